@@ -0,0 +1,58 @@
+//! Measures how fast battles are simulated without rendering them
+//!
+//! Run with `cargo bench --bench tick`
+
+use std::time::Instant;
+
+use battle_bots_engine::*;
+
+const SEEDS: u64 = 10;
+const TICKS_PER_BATTLE: usize = 500;
+
+// Swings the chainsaw every tick and wanders around, so that every actuator gets exercised.
+// Benches can't reach the crate's test helpers, keep this in sync with `wander` in there
+fn wander(game_state: &GameState, bot_position: Position) -> Actuators {
+    let directions = game_state.topology.directions();
+    let bot = game_state.bot_at(&bot_position);
+    let index = bot_position.x * 7 + bot_position.y * 3 + bot.map_or(0, |bot| bot.energy);
+
+    Actuators {
+        rotate_chainsaw: Some(Rotation::Clockwise),
+        rotate_shield: Some(Rotation::Counterclockwise),
+        move_bot: Some(directions[index % directions.len()]),
+        ..Default::default()
+    }
+}
+
+fn teams() -> Vec<ColorConfig> {
+    [Color::Blue, Color::Yellow, Color::Grey, Color::Red]
+        .into_iter()
+        .map(|color| ColorConfig {
+            color,
+            number_of_bots: 3,
+            strategy: Strategy::PerBot(wander),
+        })
+        .collect()
+}
+
+fn main() {
+    let start = Instant::now();
+    let mut ticks = 0;
+
+    for seed in 0..SEEDS {
+        let mut battle = Battle::with_seed(teams(), Rules::default(), seed);
+
+        for _ in 0..TICKS_PER_BATTLE {
+            battle.update();
+            ticks += 1;
+        }
+    }
+
+    let elapsed = start.elapsed();
+    println!(
+        "{} ticks in {:.2?}: {:.1} µs per tick",
+        ticks,
+        elapsed,
+        elapsed.as_secs_f64() * 1_000_000.0 / ticks as f64
+    );
+}
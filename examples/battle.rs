@@ -5,22 +5,22 @@ fn main() {
         ColorConfig {
             color: Color::Blue,
             number_of_bots: 3,
-            strategy: decide,
+            strategy: Strategy::PerBot(decide),
         },
         ColorConfig {
             color: Color::Yellow,
             number_of_bots: 3,
-            strategy: decide,
+            strategy: Strategy::PerBot(decide),
         },
         ColorConfig {
             color: Color::Grey,
             number_of_bots: 3,
-            strategy: decide,
+            strategy: Strategy::PerBot(decide),
         },
         ColorConfig {
             color: Color::Red,
             number_of_bots: 3,
-            strategy: decide,
+            strategy: Strategy::PerBot(decide),
         },
     ])
     .run()
@@ -43,15 +43,18 @@ pub fn decide(game_state: &GameState, bot_position: Position) -> Actuators {
       rotate_chainsaw: chainsaw_rotation,
       rotate_shield: shield_rotation,
       move_bot,
+      ..Default::default()
   }
 }
 
 fn shield_rotation(game_state: &GameState, bot_position: &Position) -> Option<Rotation> {
-  let maybe_bot = bot_in_position(game_state, &bot_position);
+  let maybe_bot = bot_in_position(game_state, bot_position);
 
   if let Some(bot) = maybe_bot {
       if let Some(adjacent_bot_direction) = adjacent_bot(game_state, bot_position) {
-          let rotation = shortest_rotation(bot.shield_direction, adjacent_bot_direction);
+          let rotation = game_state
+              .topology
+              .rotation_towards(bot.shield_direction, adjacent_bot_direction);
 
           return rotation;
       }
@@ -61,7 +64,7 @@ fn shield_rotation(game_state: &GameState, bot_position: &Position) -> Option<Ro
 }
 
 fn chainsaw_rotation(game_state: &GameState, bot_position: &Position) -> Option<Rotation> {
-  let maybe_bot = bot_in_position(game_state, &bot_position);
+  let maybe_bot = bot_in_position(game_state, bot_position);
 
   if let Some(bot) = maybe_bot {
       if let Some(adjacent_bot_direction) = adjacent_bot(game_state, bot_position) {
@@ -69,7 +72,9 @@ fn chainsaw_rotation(game_state: &GameState, bot_position: &Position) -> Option<
               return Some(Rotation::Clockwise);
           }
 
-          let rotation = shortest_rotation(bot.chainsaw_direction, adjacent_bot_direction);
+          let rotation = game_state
+              .topology
+              .rotation_towards(bot.chainsaw_direction, adjacent_bot_direction);
 
           return rotation;
       }
@@ -79,51 +84,20 @@ fn chainsaw_rotation(game_state: &GameState, bot_position: &Position) -> Option<
 }
 
 fn adjacent_bot(game_state: &GameState, bot_position: &Position) -> Option<Direction> {
-  let adjacent = valid_adjacent_positions(game_state, bot_position);
-
-  let maybe_adjacent_bot = adjacent.into_iter().find(|pos| is_bot(game_state, pos));
-
-  if let Some(adjacent_bot) = maybe_adjacent_bot {
-      if let Ok(adjacent_bot_direction) =
-          adjacent_positions_to_direction(bot_position, &adjacent_bot)
-      {
-          return Some(adjacent_bot_direction);
-      }
-  }
-  None
+  game_state
+      .adjacent_positions(bot_position)
+      .into_iter()
+      .find(|(_, pos)| is_bot(game_state, pos))
+      .map(|(direction, _)| direction)
 }
 
-// Return a vector of the adjacent positions to the given one, in the form of (x, y) tuples
-// Careful! Don't return invalid positions (negative coordinates, or coordinates that exceed the map size)
+// Return a vector of the adjacent positions to the given one, wrapping around the edges if the map does
 pub fn valid_adjacent_positions(game_state: &GameState, position: &Position) -> Vec<Position> {
-  let mut positions = vec![];
-
-  if position.x > 0 {
-      positions.push(Position {
-          x: position.x - 1,
-          y: position.y,
-      });
-  }
-  if position.x < game_state.map_width - 1 {
-      positions.push(Position {
-          x: position.x + 1,
-          y: position.y,
-      });
-  }
-  if position.y > 0 {
-      positions.push(Position {
-          x: position.x,
-          y: position.y - 1,
-      });
-  }
-  if position.y < game_state.map_height - 1 {
-      positions.push(Position {
-          x: position.x,
-          y: position.y + 1,
-      });
-  }
-
-  positions
+  game_state
+      .adjacent_positions(position)
+      .into_iter()
+      .map(|(_, adjacent)| adjacent)
+      .collect()
 }
 
 pub fn is_bot(game_state: &GameState, position: &Position) -> bool {
@@ -131,47 +105,20 @@ pub fn is_bot(game_state: &GameState, position: &Position) -> bool {
 }
 
 pub fn bot_in_position(game_state: &GameState, position: &Position) -> Option<Bot> {
-  game_state
-      .bots
-      .iter()
-      .find(|b| b.0.x == position.x && b.0.y == position.y)
-      .map(|(_, b)| b.clone())
+  game_state.bot_at(position).copied()
 }
 
 pub fn adjacent_positions_to_direction(
+  game_state: &GameState,
   from: &Position,
   to: &Position,
 ) -> Result<Direction, String> {
-  if from.x + 1 == to.x && from.y == to.y {
-      return Ok(Direction::Right);
-  } else if from.x == to.x + 1 && from.y == to.y {
-      return Ok(Direction::Left);
-  } else if from.x == to.x && from.y + 1 == to.y {
-      return Ok(Direction::Up);
-  } else if from.x == to.x && from.y == to.y + 1 {
-      return Ok(Direction::Down);
-  }
-
-  Err(String::from("Positions are not adjacent"))
-}
-
-fn shortest_rotation(from: Direction, to: Direction) -> Option<Rotation> {
-  match (from, to) {
-      _ if from == to => None,
-      (Direction::Down, Direction::Left)
-      | (Direction::Left, Direction::Up)
-      | (Direction::Up, Direction::Right)
-      | (Direction::Right, Direction::Down) => Some(Rotation::Clockwise),
-      _ => Some(Rotation::Counterclockwise),
-  }
-}
-
-pub fn absolute(n: isize) -> usize {
-  if n < 0 {
-      return -n as usize;
-  } else {
-      return n as usize;
-  }
+  game_state
+      .adjacent_positions(from)
+      .into_iter()
+      .find(|(_, adjacent)| adjacent == to)
+      .map(|(direction, _)| direction)
+      .ok_or(String::from("Positions are not adjacent"))
 }
 
 pub fn should_move_towards_enemy(
@@ -200,7 +147,7 @@ pub fn next_move_in_path(
       .ok_or(String::from("No moves to the chosen path"))?
       .clone();
 
-  adjacent_positions_to_direction(from, &first_move_position)
+  adjacent_positions_to_direction(game_state, from, &first_move_position)
 }
 
 pub fn find_shortest_path(
@@ -210,7 +157,7 @@ pub fn find_shortest_path(
 ) -> Result<Vec<Position>, String> {
   // BFS
 
-  let mut visited = vec![vec![false; game_state.map_height]; game_state.map_width];
+  let mut visited = vec![vec![false; game_state.map_height()]; game_state.map_width()];
   let mut queue: Vec<(Position, Vec<Position>)> = vec![];
 
   visited[from.x][from.y] = true;
@@ -234,7 +181,8 @@ pub fn find_shortest_path(
       let adjacents = valid_adjacent_positions(game_state, &current_pos);
 
       for adjacent_pos in adjacents {
-          if !visited[adjacent_pos.x][adjacent_pos.y] {
+          // Stay away from the cells closed by sudden death
+          if !visited[adjacent_pos.x][adjacent_pos.y] && !game_state.is_hazard(&adjacent_pos) {
               visited[adjacent_pos.x][adjacent_pos.y] = true;
 
               let mut new_path = path.clone();
@@ -252,31 +200,16 @@ pub fn find_shortest_path(
   Err("There is no available path".into())
 }
 
-pub fn distance(from_pos_x: usize, from_pos_y: usize, to_pos_x: usize, to_pos_y: usize) -> usize {
-  let x_distance = absolute(to_pos_x as isize - from_pos_x as isize);
-  let y_distance = absolute(to_pos_y as isize - from_pos_y as isize);
-
-  x_distance + y_distance
-}
-
 pub fn get_closest_enemy(game_state: &GameState, bot_position: &Position) -> Option<Position> {
+  let bot = bot_in_position(game_state, bot_position)?;
   let mut closest_enemy: Option<Position> = None;
 
-  for x in 0..game_state.map_width {
-      for y in 0..game_state.map_height {
-          if x != bot_position.x || y != bot_position.y {
-              if is_bot(game_state, &Position { x, y }) {
-                  match closest_enemy {
-                      Some(Position {
-                          x: closest_x,
-                          y: closest_y,
-                      }) if distance(closest_x, closest_y, bot_position.x, bot_position.y)
-                          < distance(x, y, bot_position.x, bot_position.y) => {}
-                      _ => closest_enemy = Some(Position { x, y }),
-                  };
-              }
-          }
-      }
+  for (position, _enemy) in game_state.enemies_of(&bot) {
+      match &closest_enemy {
+          Some(closest) if game_state.distance(closest, bot_position)
+              < game_state.distance(&position, bot_position) => {}
+          _ => closest_enemy = Some(position),
+      };
   }
 
   closest_enemy
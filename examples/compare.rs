@@ -0,0 +1,59 @@
+use std::{sync::Arc, thread};
+
+use battle_bots_engine::*;
+
+// The greedy bot of the battle example, its battle isn't run here
+#[allow(dead_code)]
+#[path = "battle.rs"]
+mod battle;
+
+// Compares the MCTS reference bot against the greedy bot and blue until it's significantly
+// better or worse than each of them
+fn main() {
+    // A lighter search than the default one, so that the comparison plays more pairs in less time
+    let search = Arc::new(Mcts {
+        iterations: 100,
+        ..Default::default()
+    });
+    let opponents: [(&str, BotStrategy); 2] = [("greedy", battle::decide), ("blue", blue)];
+
+    for (name, opponent) in opponents {
+        let comparison = Comparison {
+            max_pairs: 100,
+            threads: thread::available_parallelism().map_or(1, |threads| threads.get()),
+            ..Comparison::new(Strategy::Search(search.clone()), Strategy::PerBot(opponent))
+        };
+
+        let results = comparison.run();
+        let percent = |(low, high): (f64, f64)| format!("{:.0}%-{:.0}%", low * 100.0, high * 100.0);
+        println!(
+            "against {}: {} pairs{}",
+            name,
+            results.pairs(),
+            if results.stopped_early {
+                ", stopped early"
+            } else {
+                ""
+            }
+        );
+        println!(
+            "mcts won {:.0}% ({}), drew {:.0}% ({}), lost {:.0}% ({})",
+            results.win_rate() * 100.0,
+            percent(results.win_interval()),
+            results.draw_rate() * 100.0,
+            percent(results.draw_interval()),
+            results.loss_rate() * 100.0,
+            percent(results.loss_interval()),
+        );
+        println!(
+            "score {:.2} ({}), {}",
+            results.score(),
+            percent(results.score_interval()),
+            if results.is_significant() {
+                "significant"
+            } else {
+                "not significant"
+            }
+        );
+    }
+}
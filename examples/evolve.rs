@@ -0,0 +1,63 @@
+use std::thread;
+
+use battle_bots_engine::*;
+
+// Chases the closest enemy when it has enough energy, and goes for the closest resource otherwise.
+// The parameters are the energy to start chasing at, and how far away enemies are ignored from
+fn hunter(game_state: &GameState, bot_position: Position, parameters: &[f64]) -> Actuators {
+    let (chase_energy, sight) = (parameters[0] as usize, parameters[1] as usize);
+    let Some(bot) = game_state.bot_at(&bot_position) else {
+        return Actuators::default();
+    };
+
+    let closest_enemy = game_state
+        .enemies_of(bot)
+        .into_iter()
+        .map(|(position, _)| position)
+        .filter(|position| game_state.distance(&bot_position, position) <= sight)
+        .min_by_key(|position| game_state.distance(&bot_position, position));
+    let closest_resource = game_state
+        .resources()
+        .map(|(position, _)| position)
+        .min_by_key(|position| game_state.distance(&bot_position, position));
+    let target = match (closest_enemy, closest_resource) {
+        (Some(enemy), _) if bot.energy >= chase_energy => Some(enemy),
+        (_, Some(resource)) => Some(resource),
+        (enemy, None) => enemy,
+    };
+
+    let move_bot = target.and_then(|target| {
+        game_state
+            .adjacent_positions(&bot_position)
+            .into_iter()
+            .min_by_key(|(_, position)| game_state.distance(position, &target))
+            .map(|(direction, _)| direction)
+    });
+
+    Actuators {
+        move_bot,
+        rotate_chainsaw: Some(Rotation::Clockwise),
+        ..Default::default()
+    }
+}
+
+// Tunes the hunter against blue and writes the best parameters to best_parameters.txt
+fn main() {
+    let opponent = ColorConfig {
+        color: Color::Blue,
+        number_of_bots: 5,
+        strategy: Strategy::PerBot(blue),
+    };
+    let evolution = Evolution {
+        threads: thread::available_parallelism().map_or(1, |threads| threads.get()),
+        ..Evolution::new(hunter, vec![(0.0, 20.0), (1.0, 40.0)], vec![opponent])
+    };
+
+    let result = evolution.run();
+    for (generation, fitness) in result.best_fitness.iter().enumerate() {
+        println!("generation {}: best fitness {:.2}", generation, fitness);
+    }
+    println!("best parameters: {:?}", result.best.parameters);
+
+    write_parameters("best_parameters.txt", &result.best).expect("couldn't write the parameters");
+}
@@ -0,0 +1,47 @@
+use std::{sync::Arc, thread};
+
+use battle_bots_engine::*;
+
+// The greedy bot of the battle example, its battle isn't run here
+#[allow(dead_code)]
+#[path = "battle.rs"]
+mod battle;
+
+// Plays the MCTS reference bot against the greedy bot and blue over batches of seeded headless battles
+fn main() {
+    // Searches with the rules of each battle, and a seed mixed with the seed of the battle
+    let search = Arc::new(Mcts {
+        iterations: 300,
+        ..Default::default()
+    });
+    let threads = thread::available_parallelism().map_or(1, |threads| threads.get());
+    let opponents: [(&str, BotStrategy); 2] = [("greedy", battle::decide), ("blue", blue)];
+
+    for (name, opponent) in opponents {
+        let spec = MatchSpec {
+            colors: vec![
+                ColorConfig {
+                    color: Color::Green,
+                    number_of_bots: 5,
+                    strategy: Strategy::Search(search.clone()),
+                },
+                ColorConfig {
+                    color: Color::Blue,
+                    number_of_bots: 5,
+                    strategy: Strategy::PerBot(opponent),
+                },
+            ],
+            rules: Rules::default(),
+            max_ticks: 300,
+        };
+
+        let results = run_batch(&spec, 0..20, threads);
+        println!(
+            "mcts won {:.0}%, {} won {:.0}%, {:.0}% draws",
+            results.win_rate(Color::Green) * 100.0,
+            name,
+            results.win_rate(Color::Blue) * 100.0,
+            results.draw_rate() * 100.0
+        );
+    }
+}
@@ -1,12 +1,24 @@
 use super::{
-    state::{from_matrix, state_to_matrix, GameCell, GameState},
+    bot::Bot,
+    constants::ATTACK_DAMAGE,
     direction::{Direction, Rotation},
+    events::{EnergyCost, GameEvent},
+    feedback::{ActionFeedback, AttackOutcome, DamageTaken, MoveOutcome, ReplicationOutcome},
+    resource::Resource,
+    rules::{FriendlyFire, RepairCost, Rules, ShieldRules},
+    state::{GameCell, GameState, Position},
 };
 
+#[derive(Clone, Debug, Default)]
 pub struct Actuators {
     pub rotate_shield: Option<Rotation>,
     pub rotate_chainsaw: Option<Rotation>,
     pub move_bot: Option<Direction>,
+    // Posted to the team's message bus, teammates receive it on the next tick
+    pub send_message: Option<String>,
+    pub repair_shield: bool,
+    // Splits the bot in two, placing the new bot in the adjacent position in this direction
+    pub replicate: Option<Direction>,
 }
 
 impl Actuators {
@@ -14,85 +26,893 @@ impl Actuators {
         &self,
         bot_pos_x: usize,
         bot_pos_y: usize,
-        mut game_state: GameState,
-    ) -> GameState {
-        if let Some(rotation) = self.rotate_chainsaw {
-            game_state = rotate_chainsaw(rotation, bot_pos_x, bot_pos_y, game_state);
+        game_state: &mut GameState,
+        rules: &Rules,
+    ) -> Vec<GameEvent> {
+        let position = Position {
+            x: bot_pos_x,
+            y: bot_pos_y,
+        };
+        let bot_id = game_state.bot_at(&position).map(|bot| bot.id);
+        let mut feedback = ActionFeedback::default();
+        let mut events = vec![];
+        let mut turn_spent = false;
+        // The bot's position after moving
+        let mut final_position = position.clone();
+
+        if self.repair_shield {
+            let repaired = repair_shield(&position, game_state, &rules.shield);
+            feedback.shield_repaired = Some(repaired.unwrap_or(0));
+
+            match rules.shield.repair_cost {
+                // Repairing a full shield doesn't cost anything
+                RepairCost::Energy(cost) if repaired.is_some_and(|repaired| repaired > 0) => {
+                    spend_energy(
+                        &position,
+                        game_state,
+                        cost,
+                        EnergyCost::ShieldRepair,
+                        rules,
+                        &mut events,
+                    );
+                }
+                RepairCost::Turn => turn_spent = true,
+                _ => {}
+            }
         }
-        if let Some(rotation) = self.rotate_shield {
-            game_state = rotate_shield(rotation, bot_pos_x, bot_pos_y, game_state);
+
+        // Repairing the shield may take the whole turn
+        if !turn_spent {
+            if let Some(rotation) = self.rotate_chainsaw {
+                let outcome = rotate_chainsaw(rotation, &position, game_state, rules, &mut events);
+                feedback.attack = Some(outcome);
+
+                spend_energy(
+                    &position,
+                    game_state,
+                    rules.energy.rotation_cost,
+                    EnergyCost::Rotation,
+                    rules,
+                    &mut events,
+                );
+                // Only hits cost energy, swinging at the air or at a spared ally doesn't
+                if matches!(
+                    outcome,
+                    AttackOutcome::HitEnergy { .. } | AttackOutcome::HitShield { .. }
+                ) {
+                    spend_energy(
+                        &position,
+                        game_state,
+                        rules.energy.attack_cost,
+                        EnergyCost::Attack,
+                        rules,
+                        &mut events,
+                    );
+                }
+            }
+            if let Some(rotation) = self.rotate_shield {
+                rotate_shield(rotation, &position, game_state);
+                spend_energy(
+                    &position,
+                    game_state,
+                    rules.energy.rotation_cost,
+                    EnergyCost::Rotation,
+                    rules,
+                    &mut events,
+                );
+            }
+
+            if let Some(direction) = self.move_bot {
+                let outcome;
+                (outcome, feedback.collected) =
+                    move_bot(direction, &position, game_state, rules, &mut events);
+                feedback.movement = Some(outcome);
+
+                if outcome == MoveOutcome::Moved {
+                    if let Some(moved_to) = game_state.adjacent_position(&position, direction) {
+                        final_position = moved_to;
+                    }
+                    spend_energy(
+                        &final_position,
+                        game_state,
+                        rules.energy.move_cost,
+                        EnergyCost::Move,
+                        rules,
+                        &mut events,
+                    );
+                }
+            }
+
+            if let Some(direction) = self.replicate {
+                let outcome = replicate(direction, &final_position, game_state, rules, &mut events);
+                feedback.replication = Some(outcome);
+            }
         }
 
-        if let Some(direction) = self.move_bot {
-            game_state = move_bot(direction, bot_pos_x, bot_pos_y, game_state);
+        if self.is_idle() {
+            spend_energy(
+                &position,
+                game_state,
+                rules.energy.idle_cost,
+                EnergyCost::Idle,
+                rules,
+                &mut events,
+            );
         }
 
-        game_state
+        // Replacing the feedback also clears the damage taken since the bot's last turn
+        if let Some(bot_id) = bot_id {
+            game_state.feedback.insert(bot_id, feedback);
+        }
+
+        events
+    }
+
+    // Whether the bot does nothing with these actuators, sending messages doesn't count
+    pub fn is_idle(&self) -> bool {
+        self.rotate_shield.is_none()
+            && self.rotate_chainsaw.is_none()
+            && self.move_bot.is_none()
+            && !self.repair_shield
+            && self.replicate.is_none()
+    }
+}
+
+fn spend_energy(
+    position: &Position,
+    game_state: &mut GameState,
+    amount: usize,
+    reason: EnergyCost,
+    rules: &Rules,
+    events: &mut Vec<GameEvent>,
+) {
+    if amount == 0 {
+        return;
+    }
+
+    if let Some(bot) = game_state.bot_at_mut(position) {
+        let spent = bot.spend_energy(amount);
+        events.push(GameEvent::EnergySpent {
+            bot: bot.id,
+            amount: spent,
+            reason,
+        });
+        let bot = *bot;
+        game_state.set_cell(position, alive_or_destroyed(bot, None, rules, events));
     }
 }
 
 fn move_bot(
     direction: Direction,
-    bot_pos_x: usize,
-    bot_pos_y: usize,
-    game_state: GameState,
-) -> GameState {
-    let (final_position_x, final_position_y) = direction.compute_position(bot_pos_x, bot_pos_y);
-    let mut map = state_to_matrix(game_state);
-
-    if let GameCell::Bot(mut bot) = map[bot_pos_x][bot_pos_y] {
-        if let GameCell::Resource(r) = map[final_position_x][final_position_y] {
-            bot.gain_energy(r.energy_gain);
-
-            bot.move_step();
-            map[final_position_x][final_position_y] = GameCell::Bot(bot);
-            map[bot_pos_x][bot_pos_y] = GameCell::Empty;
-        } else if let GameCell::Empty = map[final_position_x][final_position_y] {
-            bot.move_step();
-            map[final_position_x][final_position_y] = GameCell::Bot(bot);
-            map[bot_pos_x][bot_pos_y] = GameCell::Empty;
+    position: &Position,
+    game_state: &mut GameState,
+    rules: &Rules,
+    events: &mut Vec<GameEvent>,
+) -> (MoveOutcome, Option<Resource>) {
+    let Some(mut bot) = game_state.bot_at(position).copied() else {
+        return (MoveOutcome::BlockedByMapEdge, None);
+    };
+    let Some(final_position) = game_state.adjacent_position(position, direction) else {
+        return (MoveOutcome::BlockedByMapEdge, None);
+    };
+    let mut collected = None;
+
+    match game_state.cell_at(&final_position).copied() {
+        Some(GameCell::Resource(r)) => {
+            // Bots trample the salvage claimed by other teams without gaining anything
+            if r.can_be_collected_by(&bot) {
+                bot.collect(&r, rules.energy.max_energy);
+                collected = Some(r);
+                events.push(GameEvent::ResourceCollected {
+                    bot: bot.id,
+                    resource: r,
+                });
+            }
         }
+        Some(GameCell::Empty) => {}
+        // On maps that wrap around, the target position may be the bot itself
+        Some(GameCell::Bot(blocking_bot)) if blocking_bot.id != bot.id => {
+            return (MoveOutcome::BlockedByBot(blocking_bot.id), None);
+        }
+        _ => return (MoveOutcome::BlockedByMapEdge, None),
     }
-    from_matrix(map)
+
+    bot.move_step();
+    game_state.set_cell(position, GameCell::Empty);
+    let cell = alive_or_destroyed(bot, None, rules, events);
+    game_state.set_cell(&final_position, cell);
+
+    (MoveOutcome::Moved, collected)
 }
 
-fn rotate_shield(
-    rotation: Rotation,
-    bot_pos_x: usize,
-    bot_pos_y: usize,
-    game_state: GameState,
-) -> GameState {
-    let mut map = state_to_matrix(game_state);
+fn replicate(
+    direction: Direction,
+    position: &Position,
+    game_state: &mut GameState,
+    rules: &Rules,
+    events: &mut Vec<GameEvent>,
+) -> ReplicationOutcome {
+    let Some(replication) = &rules.replication else {
+        return ReplicationOutcome::NotAllowed;
+    };
+    let Some(mut bot) = game_state.bot_at(position).copied() else {
+        return ReplicationOutcome::Blocked;
+    };
+
+    // Both halves need some energy to survive the split
+    if bot.energy <= replication.energy_threshold.max(1) {
+        return ReplicationOutcome::NotEnoughEnergy;
+    }
+    let Some(child_position) =
+        game_state
+            .adjacent_position(position, direction)
+            .filter(|child_position| {
+                matches!(game_state.cell_at(child_position), Some(GameCell::Empty))
+            })
+    else {
+        return ReplicationOutcome::Blocked;
+    };
+
+    // The child takes half of the energy and the shield of the parent, and is as tired as it
+    let mut child = Bot::new_in(game_state.new_bot_id(), bot.color, game_state.topology);
+    child.energy = bot.spend_energy(bot.energy / 2);
+    child.shield_resistance = bot.shield_resistance / 2;
+    bot.shield_resistance -= child.shield_resistance;
+    child.tiredness = bot.tiredness;
 
-    if let GameCell::Bot(mut bot) = map[bot_pos_x][bot_pos_y] {
-        bot.shield_direction = bot.shield_direction.rotate(rotation);
+    game_state.set_cell(position, GameCell::Bot(bot));
+    game_state.set_cell(&child_position, GameCell::Bot(child));
+    events.push(GameEvent::BotReplicated {
+        parent: bot.id,
+        child: child.id,
+        color: bot.color,
+    });
 
-        map[bot_pos_x][bot_pos_y] = GameCell::Bot(bot);
+    ReplicationOutcome::Replicated(child.id)
+}
+
+// Returns the shield resistance that was recovered, or None if the bot can't afford the repair
+fn repair_shield(
+    position: &Position,
+    game_state: &mut GameState,
+    rules: &ShieldRules,
+) -> Option<usize> {
+    let bot = game_state.bot_at_mut(position)?;
+    let can_afford = match rules.repair_cost {
+        RepairCost::Energy(cost) => bot.energy > cost,
+        RepairCost::Turn => true,
+    };
+
+    can_afford.then(|| bot.repair_shield(rules.repair_amount))
+}
+
+fn rotate_shield(rotation: Rotation, position: &Position, game_state: &mut GameState) {
+    let topology = game_state.topology;
+
+    if let Some(bot) = game_state.bot_at_mut(position) {
+        bot.shield_direction = topology.rotate(bot.shield_direction, rotation);
     }
-    from_matrix(map)
 }
 
 fn rotate_chainsaw(
     rotation: Rotation,
-    bot_pos_x: usize,
-    bot_pos_y: usize,
-    game_state: GameState,
-) -> GameState {
-    let mut map = state_to_matrix(game_state);
-
-    if let GameCell::Bot(mut bot) = map[bot_pos_x][bot_pos_y] {
-        bot.chainsaw_direction = bot.chainsaw_direction.rotate(rotation);
-
-        let (attacking_position_x, attacking_position_y) = bot
-            .chainsaw_direction
-            .compute_position(bot_pos_x, bot_pos_y);
-
-        if let GameCell::Bot(mut attacked_bot) = map[attacking_position_x][attacking_position_y] {
-            attacked_bot.receive_attack(bot.chainsaw_direction);
-            map[attacking_position_x][attacking_position_y] = GameCell::Bot(attacked_bot);
+    position: &Position,
+    game_state: &mut GameState,
+    rules: &Rules,
+    events: &mut Vec<GameEvent>,
+) -> AttackOutcome {
+    let topology = game_state.topology;
+    let Some(bot) = game_state.bot_at_mut(position) else {
+        return AttackOutcome::Air;
+    };
+    bot.chainsaw_direction = topology.rotate(bot.chainsaw_direction, rotation);
+    let bot = *bot;
+
+    // The chainsaw can't reach outside the map, and the bot can't attack itself
+    let Some(attacked_position) = game_state.adjacent_position(position, bot.chainsaw_direction)
+    else {
+        return AttackOutcome::Air;
+    };
+    let Some(mut attacked_bot) = game_state
+        .bot_at(&attacked_position)
+        .copied()
+        .filter(|attacked_bot| attacked_bot.id != bot.id)
+    else {
+        return AttackOutcome::Air;
+    };
+
+    let damage = match rules.friendly_fire {
+        FriendlyFire::Off if attacked_bot.is_ally_of(&bot) => {
+            return AttackOutcome::AllySpared {
+                target: attacked_bot.id,
+            };
         }
+        FriendlyFire::Reduced(damage) if attacked_bot.is_ally_of(&bot) => damage,
+        _ => ATTACK_DAMAGE,
+    };
+
+    let outcome = attacked_bot.receive_attack(bot.chainsaw_direction, damage, topology);
+    let (damage, to_shield) = match outcome {
+        AttackOutcome::HitShield { damage, .. } => (damage, true),
+        AttackOutcome::HitEnergy { damage, .. } => (damage, false),
+        AttackOutcome::AllySpared { .. } | AttackOutcome::Air => (0, false),
+    };
+    events.push(GameEvent::BotAttacked {
+        attacker: bot.id,
+        target: attacked_bot.id,
+        damage,
+        to_shield,
+    });
+
+    let cell = alive_or_destroyed(attacked_bot, Some(&bot), rules, events);
+    game_state.set_cell(&attacked_position, cell);
+
+    game_state
+        .feedback
+        .entry(attacked_bot.id)
+        .or_default()
+        .damage_taken
+        .push(DamageTaken {
+            attacker: bot.id,
+            from_direction: bot.chainsaw_direction.opposite(),
+            damage,
+            to_shield,
+        });
+
+    outcome
+}
+
+// Destroyed bots are removed from the map right away, leaving their salvage behind if any
+pub(crate) fn alive_or_destroyed(
+    bot: Bot,
+    killer: Option<&Bot>,
+    rules: &Rules,
+    events: &mut Vec<GameEvent>,
+) -> GameCell {
+    if !bot.is_destroyed() {
+        return GameCell::Bot(bot);
+    }
+    events.push(GameEvent::BotDestroyed {
+        bot: bot.id,
+        color: bot.color,
+        killer: killer.map(|killer| killer.id),
+    });
+
+    match rules
+        .salvage
+        .as_ref()
+        .and_then(|salvage| salvage.salvage_of(&bot, killer))
+    {
+        Some(salvage) => GameCell::Resource(salvage),
+        None => GameCell::Empty,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, SeedableRng};
+    use ruscii::terminal::Color;
+
+    use super::*;
+    use crate::{
+        bot::BotId,
+        constants::{
+            BOTS_STARTING_ENERGY, MAP_WIDTH, SHIELD_REPAIR_AMOUNT, STARTING_SHIELD_RESISTANCE,
+            TIREDNESS_TO_LOSE_ENERGY,
+        },
+        resource::ResourceKind,
+        rules::{
+            EnergyRules, ReplicationRules, ResourceRules, SalvageRules, SalvageValue, SpawnPolicy,
+        },
+        spawn::spawn_resources,
+        state::MapEdges,
+        test_helpers::state_with_bots,
+    };
+
+    // The chainsaw starts pointing left, so rotating it clockwise attacks the bot above
+    fn attack_ally(friendly_fire: FriendlyFire) -> (GameState, ActionFeedback) {
+        attack_ally_with(Rules {
+            friendly_fire,
+            ..Default::default()
+        })
+    }
+
+    fn attack_ally_with(rules: Rules) -> (GameState, ActionFeedback) {
+        let mut state = state_with_bots(vec![
+            (Position { x: 0, y: 0 }, Bot::new(BotId(0), Color::Blue)),
+            (Position { x: 0, y: 1 }, Bot::new(BotId(1), Color::Blue)),
+        ]);
+        let actuators = Actuators {
+            rotate_chainsaw: Some(Rotation::Clockwise),
+            ..Default::default()
+        };
+
+        actuators.execute(0, 0, &mut state, &rules);
+        let feedback = state.feedback(BotId(0)).cloned().unwrap();
+
+        (state, feedback)
+    }
+
+    fn ally_energy(state: &GameState) -> usize {
+        state.bot(BotId(1)).map_or(0, |(_, bot)| bot.energy)
+    }
+
+    fn attacker_energy(state: &GameState) -> usize {
+        state.bot(BotId(0)).map_or(0, |(_, bot)| bot.energy)
+    }
+
+    #[test]
+    fn friendly_fire_on_hurts_allies_as_much_as_enemies() {
+        let (state, feedback) = attack_ally(FriendlyFire::On);
+
+        assert_eq!(ally_energy(&state), BOTS_STARTING_ENERGY - ATTACK_DAMAGE);
+        assert!(matches!(
+            feedback.attack,
+            Some(AttackOutcome::HitEnergy {
+                target: BotId(1),
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn friendly_fire_off_spares_allies() {
+        let (state, feedback) = attack_ally(FriendlyFire::Off);
+
+        assert_eq!(ally_energy(&state), BOTS_STARTING_ENERGY);
+        assert_eq!(
+            feedback.attack,
+            Some(AttackOutcome::AllySpared { target: BotId(1) })
+        );
+    }
+
+    #[test]
+    fn reduced_friendly_fire_deals_the_reduced_damage() {
+        let (state, _) = attack_ally(FriendlyFire::Reduced(1));
+
+        assert_eq!(ally_energy(&state), BOTS_STARTING_ENERGY - 1);
+    }
+
+    #[test]
+    fn only_hits_cost_the_attack_energy() {
+        let rules = |friendly_fire| Rules {
+            friendly_fire,
+            energy: EnergyRules {
+                attack_cost: 3,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let (state, _) = attack_ally_with(rules(FriendlyFire::On));
+        assert_eq!(attacker_energy(&state), BOTS_STARTING_ENERGY - 3);
+
+        let (state, feedback) = attack_ally_with(rules(FriendlyFire::Off));
+        assert_eq!(
+            feedback.attack,
+            Some(AttackOutcome::AllySpared { target: BotId(1) })
+        );
+        assert_eq!(attacker_energy(&state), BOTS_STARTING_ENERGY);
+    }
+
+    #[test]
+    fn allies_and_enemies_never_include_the_bot_itself() {
+        let bot = Bot::new(BotId(0), Color::Blue);
+        let state = state_with_bots(vec![
+            (Position { x: 0, y: 0 }, bot),
+            (Position { x: 1, y: 0 }, Bot::new(BotId(1), Color::Blue)),
+            (Position { x: 2, y: 0 }, Bot::new(BotId(2), Color::Red)),
+            (Position { x: 3, y: 0 }, Bot::new(BotId(3), Color::Yellow)),
+        ]);
+        let ids = |bots: Vec<(Position, Bot)>| -> Vec<BotId> {
+            bots.into_iter().map(|(_, bot)| bot.id).collect()
+        };
+
+        assert_eq!(ids(state.allies_of(&bot)), vec![BotId(1)]);
+        assert_eq!(ids(state.enemies_of(&bot)), vec![BotId(2), BotId(3)]);
+    }
+
+    fn move_right_from(position: Position, blocking_cell: GameCell) -> ActionFeedback {
+        let mut state = state_with_bots(vec![(position.clone(), Bot::new(BotId(0), Color::Blue))]);
+        if let Some(target) = state.adjacent_position(&position, Direction::Right) {
+            state.set_cell(&target, blocking_cell);
+        }
+        let actuators = Actuators {
+            move_bot: Some(Direction::Right),
+            ..Default::default()
+        };
+
+        actuators.execute(position.x, position.y, &mut state, &Rules::default());
+        state.feedback(BotId(0)).cloned().unwrap()
+    }
+
+    #[test]
+    fn moves_report_what_blocked_them() {
+        let blocker = GameCell::Bot(Bot::new(BotId(1), Color::Red));
+        let feedback = move_right_from(Position { x: 0, y: 0 }, blocker);
+        assert_eq!(feedback.movement, Some(MoveOutcome::BlockedByBot(BotId(1))));
+
+        let edge = Position {
+            x: MAP_WIDTH - 1,
+            y: 0,
+        };
+        let feedback = move_right_from(edge, GameCell::Empty);
+        assert_eq!(feedback.movement, Some(MoveOutcome::BlockedByMapEdge));
+
+        let feedback = move_right_from(Position { x: 0, y: 0 }, GameCell::Empty);
+        assert_eq!(feedback.movement, Some(MoveOutcome::Moved));
+        assert!(feedback.collected.is_none());
+    }
+
+    #[test]
+    fn moving_onto_a_resource_reports_it_as_collected() {
+        let resource = Resource {
+            kind: ResourceKind::ShieldRepair,
+            amount: 2,
+            expires_at: None,
+            claimed_by: None,
+        };
+        let feedback = move_right_from(Position { x: 0, y: 0 }, GameCell::Resource(resource));
+
+        assert_eq!(feedback.movement, Some(MoveOutcome::Moved));
+        let collected = feedback.collected.unwrap();
+        assert_eq!(
+            (collected.kind, collected.amount),
+            (ResourceKind::ShieldRepair, 2)
+        );
+    }
+
+    #[test]
+    fn attacked_bots_know_where_the_hit_came_from_until_their_next_turn() {
+        // The attacker is below the target, which keeps its shield facing up
+        let (mut state, _) = attack_ally(FriendlyFire::On);
+        let feedback = state.feedback(BotId(1)).cloned().unwrap();
+        assert_eq!(
+            feedback.damage_taken,
+            vec![DamageTaken {
+                attacker: BotId(0),
+                from_direction: Direction::Down,
+                damage: ATTACK_DAMAGE,
+                to_shield: false,
+            }]
+        );
+
+        // The bot's own turn replaces the feedback, clearing the hits it already saw
+        Actuators::default().execute(0, 1, &mut state, &Rules::default());
+        assert!(state.feedback(BotId(1)).unwrap().damage_taken.is_empty());
+
+        // Facing the attacker, the next swing hits the shield
+        let target = state.bot_at_mut(&Position { x: 0, y: 1 }).unwrap();
+        target.shield_direction = Direction::Down;
+        let attacker = state.bot_at_mut(&Position { x: 0, y: 0 }).unwrap();
+        attacker.chainsaw_direction = Direction::Left;
+        Actuators {
+            rotate_chainsaw: Some(Rotation::Clockwise),
+            ..Default::default()
+        }
+        .execute(0, 0, &mut state, &Rules::default());
+        let damage_taken = &state.feedback(BotId(1)).unwrap().damage_taken;
+        assert_eq!(damage_taken.len(), 1);
+        assert_eq!(damage_taken[0].from_direction, Direction::Down);
+        assert!(damage_taken[0].to_shield);
+    }
+
+    #[test]
+    fn bots_move_and_attack_across_the_edges_of_toroidal_maps() {
+        // Rotating clockwise from down points the chainsaw left, at the bot on the other edge
+        let mut attacker = Bot::new(BotId(0), Color::Blue);
+        attacker.chainsaw_direction = Direction::Down;
+        let mut state = state_with_bots(vec![
+            (Position { x: 0, y: 0 }, attacker),
+            (Position { x: 0, y: 2 }, Bot::new(BotId(1), Color::Blue)),
+            (
+                Position {
+                    x: MAP_WIDTH - 1,
+                    y: 0,
+                },
+                Bot::new(BotId(2), Color::Red),
+            ),
+        ]);
+        state.edges = MapEdges::Toroidal;
+        let rules = Rules::default();
+
+        let swing = Actuators {
+            rotate_chainsaw: Some(Rotation::Clockwise),
+            ..Default::default()
+        };
+        swing.execute(0, 0, &mut state, &rules);
+        assert_eq!(
+            state.feedback(BotId(0)).unwrap().attack,
+            Some(AttackOutcome::HitEnergy {
+                target: BotId(2),
+                damage: ATTACK_DAMAGE,
+            })
+        );
+        let damage_taken = &state.feedback(BotId(2)).unwrap().damage_taken;
+        assert_eq!(damage_taken[0].from_direction, Direction::Right);
+
+        let move_left = Actuators {
+            move_bot: Some(Direction::Left),
+            ..Default::default()
+        };
+        move_left.execute(0, 2, &mut state, &rules);
+        assert_eq!(
+            state.feedback(BotId(1)).unwrap().movement,
+            Some(MoveOutcome::Moved)
+        );
+        let (position, _) = state.bot(BotId(1)).unwrap();
+        assert_eq!(
+            position,
+            Position {
+                x: MAP_WIDTH - 1,
+                y: 2,
+            }
+        );
+    }
+
+    #[test]
+    fn bot_destroyed_by_tiredness_is_removed_immediately() {
+        let mut bot = Bot::new(BotId(0), Color::Blue);
+        bot.energy = 1;
+        bot.tiredness = TIREDNESS_TO_LOSE_ENERGY - 1;
+        let mut state = state_with_bots(vec![(Position { x: 0, y: 0 }, bot)]);
+        let actuators = Actuators {
+            move_bot: Some(Direction::Right),
+            ..Default::default()
+        };
+
+        actuators.execute(0, 0, &mut state, &Rules::default());
+
+        assert_eq!(state.bot_count(), 0);
+    }
+
+    fn repair(repair_cost: RepairCost, energy: usize) -> (Bot, Position, ActionFeedback) {
+        let mut bot = Bot::new(BotId(0), Color::Blue);
+        bot.shield_resistance = 0;
+        bot.energy = energy;
+        let mut state = state_with_bots(vec![(Position { x: 0, y: 0 }, bot)]);
+        let rules = Rules {
+            shield: ShieldRules {
+                repair_cost,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let actuators = Actuators {
+            repair_shield: true,
+            move_bot: Some(Direction::Right),
+            ..Default::default()
+        };
+
+        actuators.execute(0, 0, &mut state, &rules);
+        let (position, bot) = state.bot(BotId(0)).unwrap();
+
+        (*bot, position, state.feedback(BotId(0)).cloned().unwrap())
+    }
+
+    #[test]
+    fn repairing_for_energy_needs_more_energy_than_the_cost() {
+        let (bot, position, feedback) = repair(RepairCost::Energy(2), BOTS_STARTING_ENERGY);
+        assert_eq!(bot.shield_resistance, SHIELD_REPAIR_AMOUNT);
+        assert_eq!(bot.energy, BOTS_STARTING_ENERGY - 2);
+        assert_eq!(feedback.shield_repaired, Some(SHIELD_REPAIR_AMOUNT));
+        // The bot still gets to move in the same turn
+        assert_eq!(position, Position { x: 1, y: 0 });
+
+        let (bot, _, feedback) = repair(RepairCost::Energy(2), 2);
+        assert_eq!(bot.shield_resistance, 0);
+        assert_eq!(bot.energy, 2);
+        assert_eq!(feedback.shield_repaired, Some(0));
+    }
+
+    #[test]
+    fn repairing_a_full_shield_costs_no_energy() {
+        let mut state = state_with_bots(vec![(
+            Position { x: 0, y: 0 },
+            Bot::new(BotId(0), Color::Blue),
+        )]);
+        let rules = Rules {
+            shield: ShieldRules {
+                repair_cost: RepairCost::Energy(2),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let actuators = Actuators {
+            repair_shield: true,
+            ..Default::default()
+        };
+
+        actuators.execute(0, 0, &mut state, &rules);
+        let (_, bot) = state.bot(BotId(0)).unwrap();
+        assert_eq!(bot.energy, BOTS_STARTING_ENERGY);
+        assert_eq!(bot.shield_resistance, STARTING_SHIELD_RESISTANCE);
+        assert_eq!(state.feedback(BotId(0)).unwrap().shield_repaired, Some(0));
+    }
+
+    #[test]
+    fn repairing_for_a_turn_skips_the_other_actuators() {
+        let (bot, position, feedback) = repair(RepairCost::Turn, BOTS_STARTING_ENERGY);
+
+        assert_eq!(bot.shield_resistance, SHIELD_REPAIR_AMOUNT);
+        assert_eq!(bot.energy, BOTS_STARTING_ENERGY);
+        assert_eq!(feedback.shield_repaired, Some(SHIELD_REPAIR_AMOUNT));
+        assert_eq!(position, Position { x: 0, y: 0 });
+        assert_eq!(feedback.movement, None);
+    }
+
+    fn replicate_right(
+        replication: Option<ReplicationRules>,
+        energy: usize,
+        blocker: Option<Bot>,
+    ) -> (GameState, ActionFeedback) {
+        let mut bot = Bot::new(BotId(0), Color::Blue);
+        bot.energy = energy;
+        bot.tiredness = 2;
+        let mut bots = vec![(Position { x: 0, y: 0 }, bot)];
+        bots.extend(blocker.map(|blocker| (Position { x: 1, y: 0 }, blocker)));
+        let mut state = state_with_bots(bots);
+        let rules = Rules {
+            replication,
+            ..Default::default()
+        };
+        let actuators = Actuators {
+            replicate: Some(Direction::Right),
+            ..Default::default()
+        };
+
+        actuators.execute(0, 0, &mut state, &rules);
+        let feedback = state.feedback(BotId(0)).cloned().unwrap();
+
+        (state, feedback)
+    }
+
+    #[test]
+    fn replication_splits_the_energy_and_the_shield() {
+        let (state, feedback) = replicate_right(Some(ReplicationRules::default()), 9, None);
+        let Some(ReplicationOutcome::Replicated(child_id)) = feedback.replication else {
+            panic!("the bot didn't replicate: {:?}", feedback.replication);
+        };
+        let (_, parent) = state.bot(BotId(0)).unwrap();
+        let (child_position, child) = state.bot(child_id).unwrap();
+
+        assert_eq!(child_position, Position { x: 1, y: 0 });
+        assert_eq!((parent.energy, child.energy), (5, 4));
+        assert_eq!(
+            parent.shield_resistance + child.shield_resistance,
+            STARTING_SHIELD_RESISTANCE
+        );
+        assert_eq!(child.shield_resistance, STARTING_SHIELD_RESISTANCE / 2);
+        assert_eq!(child.tiredness, parent.tiredness);
+    }
+
+    #[test]
+    fn replication_needs_more_energy_than_the_threshold() {
+        let rules = Some(ReplicationRules {
+            energy_threshold: 4,
+        });
+
+        let (state, feedback) = replicate_right(rules.clone(), 4, None);
+        assert_eq!(
+            feedback.replication,
+            Some(ReplicationOutcome::NotEnoughEnergy)
+        );
+        assert_eq!(state.bots().count(), 1);
+
+        let (state, feedback) = replicate_right(rules, 5, None);
+        assert!(matches!(
+            feedback.replication,
+            Some(ReplicationOutcome::Replicated(_))
+        ));
+        assert_eq!(state.bots().count(), 2);
+    }
+
+    #[test]
+    fn replication_can_be_blocked_or_not_allowed() {
+        let blocker = Bot::new(BotId(1), Color::Red);
+        let (state, feedback) =
+            replicate_right(Some(ReplicationRules::default()), 9, Some(blocker));
+        assert_eq!(feedback.replication, Some(ReplicationOutcome::Blocked));
+        assert_eq!(state.bot(BotId(0)).unwrap().1.energy, 9);
+
+        let (state, feedback) = replicate_right(None, 9, None);
+        assert_eq!(feedback.replication, Some(ReplicationOutcome::NotAllowed));
+        assert_eq!(state.bots().count(), 1);
+    }
+
+    // Kills the enemy above, which has no energy left to spare and some tiredness
+    fn kill_enemy(rules: Rules) -> GameState {
+        let mut enemy = Bot::new(BotId(1), Color::Red);
+        enemy.energy = 1;
+        enemy.shield_resistance = 0;
+        enemy.tiredness = 4;
+        let mut state = state_with_bots(vec![
+            (Position { x: 0, y: 0 }, Bot::new(BotId(0), Color::Blue)),
+            (Position { x: 0, y: 1 }, enemy),
+        ]);
+        let actuators = Actuators {
+            rotate_chainsaw: Some(Rotation::Clockwise),
+            ..Default::default()
+        };
+
+        actuators.execute(0, 0, &mut state, &rules);
+        assert!(state.bot(BotId(1)).is_none());
+
+        state
+    }
+
+    fn salvage(value: SalvageValue, killer_claims: bool) -> Option<Resource> {
+        let state = kill_enemy(Rules {
+            salvage: Some(SalvageRules {
+                value,
+                killer_claims,
+            }),
+            ..Default::default()
+        });
+
+        match state.cell_at(&Position { x: 0, y: 1 }) {
+            Some(GameCell::Resource(resource)) => Some(*resource),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn destroyed_bots_leave_salvage_worth_the_salvage_value() {
+        let bounty = salvage(SalvageValue::Bounty(7), false).unwrap();
+        assert_eq!((bounty.kind, bounty.amount), (ResourceKind::Energy, 7));
+        assert_eq!(salvage(SalvageValue::Tiredness, false).unwrap().amount, 4);
+
+        // Nothing is left when the salvage is worth nothing or disabled
+        assert!(salvage(SalvageValue::Bounty(0), false).is_none());
+        let state = kill_enemy(Rules::default());
+        assert!(matches!(
+            state.cell_at(&Position { x: 0, y: 1 }),
+            Some(GameCell::Empty)
+        ));
+    }
+
+    #[test]
+    fn only_the_killers_team_can_collect_claimed_salvage() {
+        let claimed = salvage(SalvageValue::Bounty(3), true).unwrap();
+        assert_eq!(claimed.claimed_by, Some(Color::Blue));
+        assert!(claimed.can_be_collected_by(&Bot::new(BotId(2), Color::Blue)));
+        assert!(!claimed.can_be_collected_by(&Bot::new(BotId(2), Color::Yellow)));
+
+        let unclaimed = salvage(SalvageValue::Bounty(3), false).unwrap();
+        assert!(unclaimed.can_be_collected_by(&Bot::new(BotId(2), Color::Yellow)));
+    }
+
+    #[test]
+    fn salvage_ignores_the_maximum_resources_but_counts_towards_it() {
+        let rules = Rules {
+            salvage: Some(SalvageRules::default()),
+            resources: ResourceRules {
+                spawn_policy: SpawnPolicy::Waves {
+                    every: 1,
+                    amount: 5,
+                },
+                max_resources: 1,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let mut state = state_with_bots(vec![]);
+        spawn_resources(
+            &mut state,
+            &rules.resources,
+            0,
+            &mut StdRng::seed_from_u64(0),
+        );
+        assert_eq!(state.resources().count(), 1);
 
-        map[bot_pos_x][bot_pos_y] = GameCell::Bot(bot);
+        let mut state = kill_enemy(rules.clone());
+        assert_eq!(state.resources().count(), 1);
+        spawn_resources(
+            &mut state,
+            &rules.resources,
+            0,
+            &mut StdRng::seed_from_u64(0),
+        );
+        assert_eq!(state.resources().count(), 1);
     }
-    from_matrix(map)
 }
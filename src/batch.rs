@@ -0,0 +1,219 @@
+use std::{ops::Range, sync::Mutex, thread};
+
+use ruscii::terminal::Color;
+
+use super::{battle::Battle, bot::ColorConfig, rules::Rules};
+
+/// Everything needed to play a headless battle, except for its seed
+#[derive(Clone)]
+pub struct MatchSpec {
+    pub colors: Vec<ColorConfig>,
+    pub rules: Rules,
+    // The battle ends in a draw if more than one team is left after this many ticks
+    pub max_ticks: usize,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MatchOutcome {
+    Win(Color),
+    // Several teams were left when the battle ran out of ticks, or none was left at all
+    Draw,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MatchResult {
+    pub seed: u64,
+    pub outcome: MatchOutcome,
+    pub ticks: usize,
+    // Bots left of each color at the end of the battle, in the order of the color configs
+    pub surviving_bots: Vec<(Color, usize)>,
+}
+
+/// Results of a batch of battles, the same for a given spec and seeds no matter the amount of threads
+#[derive(Clone, Debug)]
+pub struct BatchResults {
+    // Ordered by seed
+    pub matches: Vec<MatchResult>,
+    // Battles won by each color, in the order of the color configs
+    pub wins: Vec<(Color, usize)>,
+    pub draws: usize,
+}
+
+impl BatchResults {
+    pub fn win_rate(&self, color: Color) -> f64 {
+        if self.matches.is_empty() {
+            return 0.0;
+        }
+        let wins = self
+            .wins
+            .iter()
+            .find(|(c, _)| *c == color)
+            .map_or(0, |(_, wins)| *wins);
+
+        wins as f64 / self.matches.len() as f64
+    }
+
+    pub fn draw_rate(&self) -> f64 {
+        if self.matches.is_empty() {
+            return 0.0;
+        }
+
+        self.draws as f64 / self.matches.len() as f64
+    }
+}
+
+// Plays a battle without rendering it until a single team is left or it runs out of ticks
+pub fn play_match(spec: &MatchSpec, seed: u64) -> MatchResult {
+    let mut battle = Battle::with_seed(spec.colors.clone(), spec.rules.clone(), seed);
+    while !is_over(&battle, spec) {
+        battle.update();
+    }
+
+    match_result(&battle, spec, seed)
+}
+
+pub(crate) fn is_over(battle: &Battle, spec: &MatchSpec) -> bool {
+    battle.tick() >= spec.max_ticks || battle.remaining_teams().len() <= 1
+}
+
+pub(crate) fn match_result(battle: &Battle, spec: &MatchSpec, seed: u64) -> MatchResult {
+    let outcome = match battle.remaining_teams().as_slice() {
+        [winner] => MatchOutcome::Win(*winner),
+        _ => MatchOutcome::Draw,
+    };
+    let surviving_bots = spec
+        .colors
+        .iter()
+        .map(|color_config| {
+            let bots = battle
+                .state
+                .bots()
+                .filter(|(_, bot)| bot.color == color_config.color)
+                .count();
+
+            (color_config.color, bots)
+        })
+        .collect();
+
+    MatchResult {
+        seed,
+        outcome,
+        ticks: battle.tick(),
+        surviving_bots,
+    }
+}
+
+// Plays a battle for each seed, spread over the given amount of threads
+pub fn run_batch(spec: &MatchSpec, seeds: Range<u64>, threads: usize) -> BatchResults {
+    let pending = Mutex::new(seeds);
+    let mut matches: Vec<MatchResult> = thread::scope(|scope| {
+        let workers: Vec<_> = (0..threads.max(1))
+            .map(|_| {
+                let pending = &pending;
+
+                scope.spawn(move || {
+                    let mut results = vec![];
+                    while let Some(seed) = next_seed(pending) {
+                        results.push(play_match(spec, seed));
+                    }
+
+                    results
+                })
+            })
+            .collect();
+
+        workers
+            .into_iter()
+            .flat_map(|worker| {
+                worker
+                    .join()
+                    .unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+            })
+            .collect()
+    });
+    matches.sort_by_key(|result| result.seed);
+
+    let wins = spec
+        .colors
+        .iter()
+        .map(|color_config| {
+            let wins = matches
+                .iter()
+                .filter(|result| result.outcome == MatchOutcome::Win(color_config.color))
+                .count();
+
+            (color_config.color, wins)
+        })
+        .collect();
+    let draws = matches
+        .iter()
+        .filter(|result| result.outcome == MatchOutcome::Draw)
+        .count();
+
+    BatchResults {
+        matches,
+        wins,
+        draws,
+    }
+}
+
+// The lock is released before playing the match for the seed
+fn next_seed(pending: &Mutex<Range<u64>>) -> Option<u64> {
+    pending.lock().ok()?.next()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        actuators::Actuators,
+        bot::Strategy,
+        direction::{Direction, Rotation},
+        state::{GameState, Position},
+    };
+
+    fn charge(_game_state: &GameState, bot_position: Position) -> Actuators {
+        Actuators {
+            rotate_chainsaw: Some(Rotation::Clockwise),
+            move_bot: Some(if bot_position.x.is_multiple_of(2) {
+                Direction::Right
+            } else {
+                Direction::Up
+            }),
+            ..Default::default()
+        }
+    }
+
+    fn spec() -> MatchSpec {
+        MatchSpec {
+            colors: [Color::Blue, Color::Red]
+                .into_iter()
+                .map(|color| ColorConfig {
+                    color,
+                    number_of_bots: 3,
+                    strategy: Strategy::PerBot(charge),
+                })
+                .collect(),
+            rules: Rules::default(),
+            max_ticks: 100,
+        }
+    }
+
+    #[test]
+    fn battles_can_be_sent_across_threads() {
+        fn assert_send<T: Send>() {}
+
+        assert_send::<Battle>();
+        assert_send::<MatchSpec>();
+    }
+
+    #[test]
+    fn results_do_not_depend_on_the_amount_of_threads() {
+        let sequential = run_batch(&spec(), 0..12, 1);
+        let parallel = run_batch(&spec(), 0..12, 4);
+
+        assert_eq!(sequential.matches, parallel.matches);
+        assert_eq!(sequential.wins, parallel.wins);
+        assert_eq!(sequential.draws, parallel.draws);
+    }
+}
@@ -1,4 +1,6 @@
-use rand::Rng;
+use std::{collections::HashMap, thread};
+
+use rand::{rngs::StdRng, SeedableRng};
 use ruscii::{
     app::{App, Config, State},
     drawing::{Pencil, RectCharset},
@@ -9,33 +11,73 @@ use ruscii::{
 };
 
 use crate::{
-    bot::{Bot, BotStrategy, ColorConfig},
+    actuators::Actuators,
+    bot::{Bot, BotId, ColorConfig, Strategy},
     constants::*,
+    dataset::DecisionRecord,
     direction::Direction,
-    resource::Resource,
-    state::{from_matrix, state_to_matrix, GameCell, GameState, Position},
+    environment::observe,
+    events::GameEvent,
+    resource::ResourceKind,
+    rules::{Decisions, Rules},
+    simulation::play_tick,
+    spawn::find_empty_position,
+    state::{from_matrix, GameCell, GameState, MapEdges, Position},
+    topology::Topology,
 };
 
 pub struct Battle {
     pub state: GameState,
     pub colors: Vec<ColorConfig>,
+    pub rules: Rules,
+    // Every event that happened in the battle, along with the tick it happened in
+    pub events: Vec<(usize, GameEvent)>,
+    // Threads to call the strategies in when decisions are made on a snapshot, the outcome of the
+    // battle doesn't depend on it
+    pub threads: usize,
+    // Decisions of the strategies are logged here if it's set, until `write_decisions` takes them
+    pub decision_log: Option<Vec<DecisionRecord>>,
+    // Used for everything random in the battle, so that battles with the same seed play out the same
+    pub(crate) rng: StdRng,
+    // Search strategies get their seeds from this one, without drawing from the random number generator
+    pub(crate) seed: u64,
 }
 
 impl Battle {
     pub fn new(colors: Vec<ColorConfig>) -> Battle {
-        let mut map = [[GameCell::Empty; MAP_HEIGHT]; MAP_WIDTH];
+        Battle::with_rules(colors, Rules::default())
+    }
+
+    pub fn with_rules(colors: Vec<ColorConfig>, rules: Rules) -> Battle {
+        Battle::with_seed(colors, rules, rand::random())
+    }
+
+    pub fn with_seed(colors: Vec<ColorConfig>, rules: Rules, seed: u64) -> Battle {
+        let mut state = from_matrix([[GameCell::Empty; MAP_HEIGHT]; MAP_WIDTH]);
+        state.edges = rules.edges;
+        state.topology = rules.topology;
+        state.teams = colors.iter().map(|c| c.color).collect();
+        let mut rng = StdRng::seed_from_u64(seed);
 
         for color_config in colors.iter() {
             for _ in 0..color_config.number_of_bots {
-                if let Some(Position { x, y }) = find_empty_position(&map) {
-                    map[x][y] = GameCell::Bot(Bot::new(color_config.color));
+                if let Some(position) = find_empty_position(&state, &mut rng) {
+                    let bot = Bot::new_in(state.new_bot_id(), color_config.color, rules.topology);
+                    state.set_cell(&position, GameCell::Bot(bot));
                 }
             }
         }
 
-        let state = from_matrix(map);
-
-        Battle { state, colors }
+        Battle {
+            state,
+            colors,
+            rules,
+            events: vec![],
+            threads: 1,
+            decision_log: None,
+            rng,
+            seed,
+        }
     }
 
     pub fn run(&mut self) {
@@ -57,72 +99,112 @@ impl Battle {
 
             let mut pencil = Pencil::new(window.canvas_mut());
 
+            // Dotted borders show that bots can go across them
+            let border = match self.state.edges {
+                MapEdges::Bounded => RectCharset::double_lines(),
+                MapEdges::Toroidal => RectCharset::from("┈┈┊┊╭╮╰╯"),
+            };
+
             pencil
-                .set_origin(Vec2::xy(1 as usize, 1 as usize))
+                .set_origin(Vec2::xy(1, 1))
                 .set_foreground(Color::Grey)
                 .draw_rect(
-                    &RectCharset::double_lines(),
-                    Vec2::xy(-1 as isize, -1 as isize),
+                    &border,
+                    Vec2::xy(-1, -1),
                     Vec2::xy(MAP_WIDTH * 3 + 2, MAP_HEIGHT * 3 + 2),
                 );
 
-            let map = state_to_matrix(self.state.clone());
+            // Odd rows of hex maps are shifted half a cell to the right
+            let topology = self.state.topology;
+            let screen_x = |x: usize, y: usize| match topology {
+                Topology::Hex if y % 2 == 1 => x * 3 + 1,
+                _ => x * 3,
+            };
 
             for x in 0..MAP_WIDTH {
                 for y in 0..MAP_HEIGHT {
-                    if let GameCell::Bot(bot) = map[x][y] {
+                    let Some(cell) = self.state.cell_at(&Position { x, y }) else {
+                        continue;
+                    };
+
+                    // Bots and resources are drawn over the hazard marker
+                    if self.state.is_hazard(&Position { x, y }) {
+                        pencil.set_foreground(Color::Red);
+                        pencil.draw_char('░', Vec2::xy(screen_x(x, y), (MAP_HEIGHT - 1 - y) * 3));
+                    }
+
+                    if let GameCell::Bot(bot) = cell {
                         pencil.set_foreground(bot.color);
-                        pencil.draw_char(
-                            format!("{}", bot.energy).as_str().chars().next().unwrap(),
-                            Vec2::xy(x * 3, (MAP_HEIGHT - 1 - y) * 3),
-                        );
-                        let bot_down = Vec2::xy(
-                            (x as i32) * 3,
-                            ((MAP_HEIGHT as i32) - (y as i32) - 1) * 3 + 1,
-                        );
-                        let bot_up = Vec2::xy(
-                            (x as i32) * 3,
-                            ((MAP_HEIGHT as i32) - (y as i32) - 1) * 3 - 1,
-                        );
-                        let bot_left = Vec2::xy(
-                            (x as i32) * 3 - 1,
-                            ((MAP_HEIGHT as i32) - (y as i32) - 1) * 3,
-                        );
-                        let bot_right = Vec2::xy(
-                            (x as i32) * 3 + 1,
-                            ((MAP_HEIGHT as i32) - (y as i32) - 1) * 3,
+                        pencil.draw_center_text(
+                            format!("{}", bot.energy).as_str(),
+                            Vec2::xy(screen_x(x, y), (MAP_HEIGHT - 1 - y) * 3),
                         );
+                        // Each cell is 3x3 characters, with the bot in the middle
+                        let next_to_bot = |direction: Direction| {
+                            let (dx, dy) = direction.offset();
+
+                            Vec2::xy(
+                                screen_x(x, y) as i32 + dx as i32,
+                                ((MAP_HEIGHT as i32) - (y as i32) - 1) * 3 - dy as i32,
+                            )
+                        };
+                        let shield = next_to_bot(bot.shield_direction);
+                        let chainsaw = next_to_bot(bot.chainsaw_direction);
 
                         if !bot.is_shield_destroyed()
                             && bot.shield_direction.eq(&bot.chainsaw_direction)
                         {
                             match bot.shield_direction {
-                                Direction::Down => pencil.draw_char('⤈', bot_down),
-                                Direction::Up => pencil.draw_char('⤉', bot_up),
-                                Direction::Left => pencil.draw_char('⇷', bot_left),
-                                Direction::Right => pencil.draw_char('⇸', bot_right),
+                                Direction::Down => pencil.draw_char('⤈', shield),
+                                Direction::Up => pencil.draw_char('⤉', shield),
+                                Direction::Left => pencil.draw_char('⇷', shield),
+                                Direction::Right => pencil.draw_char('⇸', shield),
+                                Direction::UpRight => pencil.draw_char('⇗', shield),
+                                Direction::UpLeft => pencil.draw_char('⇖', shield),
+                                Direction::DownRight => pencil.draw_char('⇘', shield),
+                                Direction::DownLeft => pencil.draw_char('⇙', shield),
                             };
                         } else {
                             if !bot.is_shield_destroyed() {
                                 match bot.shield_direction {
-                                    Direction::Down => pencil.draw_char('—', bot_down),
-                                    Direction::Up => pencil.draw_char('—', bot_up),
-                                    Direction::Left => pencil.draw_char('|', bot_left),
-                                    Direction::Right => pencil.draw_char('|', bot_right),
+                                    Direction::Down | Direction::Up => {
+                                        pencil.draw_char('—', shield)
+                                    }
+                                    Direction::Left | Direction::Right => {
+                                        pencil.draw_char('|', shield)
+                                    }
+                                    Direction::UpRight | Direction::DownLeft => {
+                                        pencil.draw_char('/', shield)
+                                    }
+                                    Direction::UpLeft | Direction::DownRight => {
+                                        pencil.draw_char('\\', shield)
+                                    }
                                 };
                             }
                             match bot.chainsaw_direction {
-                                Direction::Down => pencil.draw_char('↓', bot_down),
-                                Direction::Up => pencil.draw_char('↑', bot_up),
-                                Direction::Left => pencil.draw_char('←', bot_left),
-                                Direction::Right => pencil.draw_char('→', bot_right),
+                                Direction::Down => pencil.draw_char('↓', chainsaw),
+                                Direction::Up => pencil.draw_char('↑', chainsaw),
+                                Direction::Left => pencil.draw_char('←', chainsaw),
+                                Direction::Right => pencil.draw_char('→', chainsaw),
+                                Direction::UpRight => pencil.draw_char('↗', chainsaw),
+                                Direction::UpLeft => pencil.draw_char('↖', chainsaw),
+                                Direction::DownRight => pencil.draw_char('↘', chainsaw),
+                                Direction::DownLeft => pencil.draw_char('↙', chainsaw),
                             };
                         }
-                    } else if let GameCell::Resource(resource) = &map[x][y] {
-                        pencil.set_foreground(Color::White);
+                    } else if let GameCell::Resource(resource) = cell {
+                        pencil.set_foreground(match resource.kind {
+                            ResourceKind::Energy => Color::White,
+                            ResourceKind::ShieldRepair => Color::Cyan,
+                            ResourceKind::TirednessReset => Color::Green,
+                        });
+                        let text = match resource.kind {
+                            ResourceKind::TirednessReset => String::from("z"),
+                            _ => format!("{}", resource.amount),
+                        };
                         pencil.draw_center_text(
-                            format!("{}", resource.energy_gain).as_str(),
-                            Vec2::xy(x * 3, (MAP_HEIGHT - 1 - y) * 3),
+                            text.as_str(),
+                            Vec2::xy(screen_x(x, y), (MAP_HEIGHT - 1 - y) * 3),
                         );
                     }
                 }
@@ -130,64 +212,475 @@ impl Battle {
         });
     }
 
-    fn strategy_for(&self, color: Color) -> Option<BotStrategy> {
+    // Ticks played so far
+    pub fn tick(&self) -> usize {
+        self.state.tick()
+    }
+
+    // Colors that still have bots on the map, in the order of the color configs
+    pub fn remaining_teams(&self) -> Vec<Color> {
         self.colors
             .iter()
-            .find(|c| c.color == color)
-            .map(|c| c.strategy)
+            .map(|color_config| color_config.color)
+            .filter(|color| self.state.bots().any(|(_, bot)| bot.color == *color))
+            .collect()
     }
 
-    fn update(&mut self) {
-        let old_map = state_to_matrix(self.state.clone());
+    // Plays a single tick of the battle without rendering it
+    pub fn update(&mut self) {
+        self.update_with(&HashMap::new());
+    }
 
-        for x in 0..MAP_WIDTH {
-            for y in 0..MAP_HEIGHT {
-                if let GameCell::Bot(bot) = old_map[x][y] {
-                    if let Some(strategy) = self.strategy_for(bot.color) {
-                        let actuators = (strategy)(&self.state, Position { x, y });
-                        self.state = actuators.execute(x, y, self.state.clone());
-                    }
+    // Plays a tick where the given bots use the given actuators instead of asking their strategies
+    pub(crate) fn update_with(&mut self, actuators: &HashMap<BotId, Actuators>) {
+        // The colors can be changed from outside of the battle, the state gets them before every tick
+        self.state.teams = self.colors.iter().map(|c| c.color).collect();
+
+        let (colors, threads, decisions) = (&self.colors, self.threads, self.rules.decisions);
+        let (rules, seed, decision_log) = (&self.rules, self.seed, &mut self.decision_log);
+        let mut team_decisions = vec![];
+        // Decisions made at the start of the tick, along with the state they were made on if it's logged
+        let mut snapshot: Option<(HashMap<BotId, Actuators>, Option<GameState>)> = None;
+        let tick = self.state.tick();
+        let events = play_tick(
+            &mut self.state,
+            &self.rules,
+            &mut self.rng,
+            |state, bot, position| {
+                // Nothing has happened yet in the tick when the first bot gets its turn
+                if decisions == Decisions::Snapshot && snapshot.is_none() {
+                    let snapshot_state = decision_log.is_some().then(|| state.clone());
+                    let decisions = decide_on_snapshot(state, colors, rules, seed, threads);
+                    snapshot = Some((decisions, snapshot_state));
                 }
-            }
-        }
-        let mut map = state_to_matrix(self.state.clone());
 
-        for x in 0..MAP_WIDTH {
-            for y in 0..MAP_HEIGHT {
-                if let GameCell::Bot(bot) = map[x][y] {
-                    if bot.energy <= 0 {
-                        map[x][y] = GameCell::Empty;
-                    }
+                let decision = match (actuators.get(&bot.id), &mut snapshot) {
+                    (Some(actuators), _) => Some(actuators.clone()),
+                    (None, Some((snapshot_decisions, _))) => snapshot_decisions.remove(&bot.id),
+                    (None, None) => decide(
+                        state,
+                        colors,
+                        rules,
+                        seed,
+                        bot,
+                        position.clone(),
+                        &mut team_decisions,
+                    ),
+                };
+
+                if let (Some(log), Some(actuators)) = (decision_log.as_mut(), &decision) {
+                    let seen = match &snapshot {
+                        Some((_, Some(snapshot_state))) => snapshot_state,
+                        _ => state,
+                    };
+                    log.push(DecisionRecord {
+                        tick: state.tick(),
+                        bot: *bot,
+                        position: position.clone(),
+                        observation: observe(seen, bot.color, rules),
+                        actuators: actuators.clone(),
+                    });
                 }
+
+                decision
+            },
+        );
+
+        self.events
+            .extend(events.into_iter().map(|event| (tick, event)));
+    }
+}
+
+fn strategy_for(colors: &[ColorConfig], color: Color) -> Option<&Strategy> {
+    colors
+        .iter()
+        .find(|c| c.color == color)
+        .map(|c| &c.strategy)
+}
+
+// Team strategies are called once per tick, when the first bot of the team gets its turn
+fn decide(
+    state: &GameState,
+    colors: &[ColorConfig],
+    rules: &Rules,
+    seed: u64,
+    bot: &Bot,
+    position: Position,
+    team_decisions: &mut Vec<(Color, HashMap<BotId, Actuators>)>,
+) -> Option<Actuators> {
+    match strategy_for(colors, bot.color)? {
+        Strategy::PerBot(strategy) => Some((strategy)(state, position)),
+        Strategy::Parameterized(strategy, parameters) => {
+            Some((strategy)(state, position, parameters))
+        }
+        Strategy::Search(search) => Some(search.decide_in_battle(state, position, rules, seed)),
+        Strategy::Team(strategy) => {
+            if !team_decisions.iter().any(|(color, _)| *color == bot.color) {
+                let team_bots: Vec<(Position, Bot)> = state
+                    .bots()
+                    .filter(|(_, b)| b.color == bot.color)
+                    .map(|(position, b)| (position, *b))
+                    .collect();
+
+                team_decisions.push((bot.color, (strategy)(state, &team_bots)));
             }
+
+            team_decisions
+                .iter_mut()
+                .find(|(color, _)| *color == bot.color)
+                .and_then(|(_, decisions)| decisions.remove(&bot.id))
         }
-        let mut rng = rand::thread_rng();
-        let generated_resources = rng.gen_range(0..(AVERAGE_RESOURCE_GENERATION_PER_TICK * 2));
+    }
+}
+
+// Decides for every bot on the state at the start of the tick, with the strategies spread over
+// the given threads. Each team strategy is called once, in the scan order of the first bot of the team
+fn decide_on_snapshot(
+    state: &GameState,
+    colors: &[ColorConfig],
+    rules: &Rules,
+    seed: u64,
+    threads: usize,
+) -> HashMap<BotId, Actuators> {
+    let mut calls: Vec<(&Strategy, Vec<(Position, Bot)>)> = vec![];
 
-        if self.state.resources.len() < MAX_RESOURCES {
-            for _ in 0..generated_resources {
-                if let Some(Position { x, y }) = find_empty_position(&map) {
-                    let energy_gain =
-                        rng.gen_range(RESOURCE_MIN_ENERGY_GAIN..RESOURCE_MAX_ENERGY_GAIN);
+    for (position, bot) in state.bots() {
+        match strategy_for(colors, bot.color) {
+            Some(strategy @ Strategy::Team(_)) => {
+                let already_called = calls.iter().any(|(strategy, bots)| {
+                    matches!(strategy, Strategy::Team(_))
+                        && bots.iter().any(|(_, b)| b.color == bot.color)
+                });
 
-                    map[x][y] = GameCell::Resource(Resource { energy_gain });
+                if !already_called {
+                    let team_bots = state
+                        .bots()
+                        .filter(|(_, b)| b.color == bot.color)
+                        .map(|(position, b)| (position, *b))
+                        .collect();
+                    calls.push((strategy, team_bots));
                 }
             }
+            Some(strategy) => calls.push((strategy, vec![(position, *bot)])),
+            None => {}
         }
+    }
 
-        self.state = from_matrix(map);
+    let call = |(strategy, bots): &(&Strategy, Vec<(Position, Bot)>)| match strategy {
+        Strategy::PerBot(strategy) => bots
+            .iter()
+            .map(|(position, bot)| (bot.id, (strategy)(state, position.clone())))
+            .collect::<Vec<_>>(),
+        Strategy::Parameterized(strategy, parameters) => bots
+            .iter()
+            .map(|(position, bot)| (bot.id, (strategy)(state, position.clone(), parameters)))
+            .collect(),
+        Strategy::Search(search) => bots
+            .iter()
+            .map(|(position, bot)| {
+                let actuators = search.decide_in_battle(state, position.clone(), rules, seed);
+
+                (bot.id, actuators)
+            })
+            .collect(),
+        Strategy::Team(strategy) => (strategy)(state, bots)
+            .into_iter()
+            // Team strategies can only decide for their own bots
+            .filter(|(bot_id, _)| bots.iter().any(|(_, bot)| bot.id == *bot_id))
+            .collect(),
+    };
+
+    if threads <= 1 {
+        return calls.iter().flat_map(call).collect();
     }
+
+    let chunk_size = calls.len().div_ceil(threads).max(1);
+    thread::scope(|scope| {
+        let workers: Vec<_> = calls
+            .chunks(chunk_size)
+            .map(|chunk| scope.spawn(|| chunk.iter().flat_map(call).collect::<Vec<_>>()))
+            .collect();
+
+        workers
+            .into_iter()
+            .flat_map(|worker| {
+                worker
+                    .join()
+                    .unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+            })
+            .collect()
+    })
 }
 
-fn find_empty_position(map: &[[GameCell; MAP_HEIGHT]; MAP_WIDTH]) -> Option<Position> {
-    let mut rng = rand::thread_rng();
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
 
-    loop {
-        let x: usize = rng.gen_range(0..MAP_WIDTH);
-        let y: usize = rng.gen_range(0..MAP_HEIGHT);
+    use super::*;
+    use crate::{
+        actuators::Actuators,
+        direction::Rotation,
+        feedback::AttackOutcome,
+        rules::{HazardPenalty, Initiative, SuddenDeathRules},
+        simulation::turn_order,
+        test_helpers::{idle, state_with_bots, wander},
+    };
 
-        if let GameCell::Empty = map[x][y] {
-            return Some(Position { x, y });
+    fn swing_chainsaw(_game_state: &GameState, _bot_position: Position) -> Actuators {
+        Actuators {
+            rotate_chainsaw: Some(Rotation::Clockwise),
+            ..Default::default()
         }
     }
+
+    fn battle_with_bots(bots: Vec<(Position, Bot)>) -> Battle {
+        let mut battle = Battle::new(vec![ColorConfig {
+            color: Color::Blue,
+            number_of_bots: 0,
+            strategy: Strategy::PerBot(swing_chainsaw),
+        }]);
+        battle.state = state_with_bots(bots);
+
+        battle
+    }
+
+    #[test]
+    fn bot_destroyed_mid_tick_does_not_act() {
+        // The chainsaw starts pointing left, so after rotating clockwise each bot attacks upwards
+        let attacker = Bot::new(BotId(0), Color::Blue);
+        let mut victim = Bot::new(BotId(1), Color::Blue);
+        victim.energy = ATTACK_DAMAGE;
+        victim.shield_direction = Direction::Left;
+        let bystander = Bot::new(BotId(2), Color::Blue);
+
+        let mut battle = battle_with_bots(vec![
+            (Position { x: 0, y: 0 }, attacker),
+            (Position { x: 0, y: 1 }, victim),
+            (Position { x: 0, y: 2 }, bystander),
+        ]);
+        battle.update();
+
+        assert!(battle.state.find_bot(BotId(1)).is_none());
+        let (_, bystander) = battle.state.find_bot(BotId(2)).unwrap();
+        assert_eq!(bystander.energy, BOTS_STARTING_ENERGY);
+        assert_eq!(bystander.shield_resistance, STARTING_SHIELD_RESISTANCE);
+    }
+
+    #[test]
+    fn bots_outside_the_shrinking_arena_are_penalized() {
+        let mut battle = battle_with_bots(vec![
+            (Position { x: 0, y: 0 }, Bot::new(BotId(0), Color::Blue)),
+            (Position { x: 15, y: 5 }, Bot::new(BotId(1), Color::Blue)),
+        ]);
+        battle.rules.sudden_death = Some(SuddenDeathRules {
+            start_tick: 0,
+            ticks_per_ring: 1,
+            penalty: HazardPenalty::Death,
+        });
+        battle.update();
+
+        assert!(battle.state.is_hazard(&Position { x: 0, y: 0 }));
+        assert!(battle.state.find_bot(BotId(0)).is_none());
+        assert!(battle.state.find_bot(BotId(1)).is_some());
+    }
+
+    #[test]
+    fn round_robin_initiative_alternates_teams() {
+        let mut battle = battle_with_bots(vec![
+            (Position { x: 0, y: 0 }, Bot::new(BotId(0), Color::Blue)),
+            (Position { x: 0, y: 1 }, Bot::new(BotId(1), Color::Blue)),
+            (Position { x: 1, y: 0 }, Bot::new(BotId(2), Color::Red)),
+        ]);
+        battle.colors.push(ColorConfig {
+            color: Color::Red,
+            number_of_bots: 0,
+            strategy: Strategy::PerBot(swing_chainsaw),
+        });
+        battle.rules.initiative = Initiative::RoundRobin;
+        battle.state.teams = vec![Color::Blue, Color::Red];
+
+        let turn_order =
+            |battle: &mut Battle| turn_order(&battle.state, &battle.rules, &mut battle.rng);
+        assert_eq!(turn_order(&mut battle), vec![BotId(0), BotId(2), BotId(1)]);
+        battle.state.tick = 1;
+        assert_eq!(turn_order(&mut battle), vec![BotId(2), BotId(0), BotId(1)]);
+    }
+
+    #[test]
+    fn random_initiative_follows_the_seed() {
+        let bots = (0..6)
+            .map(|id| (Position { x: id, y: 0 }, Bot::new(BotId(id), Color::Blue)))
+            .collect();
+        let mut battle = battle_with_bots(bots);
+        battle.rules.initiative = Initiative::Random;
+
+        let turn_order = |battle: &Battle, seed| {
+            turn_order(
+                &battle.state,
+                &battle.rules,
+                &mut StdRng::seed_from_u64(seed),
+            )
+        };
+        let order = turn_order(&battle, 3);
+        assert_eq!(order, turn_order(&battle, 3));
+
+        let mut sorted = order.clone();
+        sorted.sort_by_key(|bot_id| bot_id.0);
+        assert_eq!(sorted, (0..6).map(BotId).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn energy_initiative_breaks_ties_in_scan_order() {
+        let with_energy = |id, energy| {
+            let mut bot = Bot::new(BotId(id), Color::Blue);
+            bot.energy = energy;
+            bot
+        };
+        let mut battle = battle_with_bots(vec![
+            (Position { x: 0, y: 0 }, with_energy(0, 5)),
+            (Position { x: 0, y: 1 }, with_energy(1, 9)),
+            (Position { x: 1, y: 0 }, with_energy(2, 5)),
+            (Position { x: 2, y: 0 }, with_energy(3, 9)),
+        ]);
+        battle.rules.initiative = Initiative::ByEnergy;
+
+        let order = turn_order(&battle.state, &battle.rules, &mut battle.rng);
+        assert_eq!(order, vec![BotId(1), BotId(3), BotId(0), BotId(2)]);
+    }
+
+    #[test]
+    fn the_turn_order_of_every_tick_is_recorded() {
+        let mut battle = battle_with_bots(vec![
+            (Position { x: 0, y: 0 }, Bot::new(BotId(0), Color::Blue)),
+            (Position { x: 4, y: 0 }, Bot::new(BotId(1), Color::Blue)),
+        ]);
+        battle.update();
+        battle.update();
+
+        let turn_orders: Vec<(usize, Vec<BotId>)> = battle
+            .events
+            .iter()
+            .filter_map(|(tick, event)| match event {
+                GameEvent::TurnOrder { bots } => Some((*tick, bots.clone())),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            turn_orders,
+            vec![(0, vec![BotId(0), BotId(1)]), (1, vec![BotId(0), BotId(1)])]
+        );
+    }
+
+    static TEAM_CALLS: AtomicUsize = AtomicUsize::new(0);
+
+    // Counts its calls and tries to move every bot on the map up, even the ones of other teams
+    fn move_everyone_up(
+        game_state: &GameState,
+        _team_bots: &[(Position, Bot)],
+    ) -> HashMap<BotId, Actuators> {
+        TEAM_CALLS.fetch_add(1, Ordering::SeqCst);
+
+        game_state
+            .bots()
+            .map(|(_, bot)| {
+                let actuators = Actuators {
+                    move_bot: Some(Direction::Up),
+                    ..Default::default()
+                };
+
+                (bot.id, actuators)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn team_strategies_decide_once_per_tick_only_for_their_own_bots() {
+        let mut battle = battle_with_bots(vec![
+            (Position { x: 0, y: 0 }, Bot::new(BotId(0), Color::Blue)),
+            (Position { x: 0, y: 2 }, Bot::new(BotId(1), Color::Blue)),
+            (Position { x: 5, y: 5 }, Bot::new(BotId(2), Color::Red)),
+        ]);
+        battle.colors[0].strategy = Strategy::Team(move_everyone_up);
+        battle.colors.push(ColorConfig {
+            color: Color::Red,
+            number_of_bots: 0,
+            strategy: Strategy::PerBot(swing_chainsaw),
+        });
+
+        battle.update();
+        battle.update();
+
+        assert_eq!(TEAM_CALLS.load(Ordering::SeqCst), 2);
+        assert_eq!(
+            battle.state.find_bot(BotId(0)).unwrap().0,
+            Position { x: 0, y: 2 }
+        );
+        assert_eq!(
+            battle.state.find_bot(BotId(1)).unwrap().0,
+            Position { x: 0, y: 4 }
+        );
+        // The bot of the other team keeps its own strategy's actuators
+        let (position, _) = battle.state.find_bot(BotId(2)).unwrap();
+        assert_eq!(position, Position { x: 5, y: 5 });
+        let feedback = battle.state.feedback(BotId(2)).unwrap();
+        assert_eq!(feedback.movement, None);
+        assert_eq!(feedback.attack, Some(AttackOutcome::Air));
+    }
+
+    #[test]
+    fn snapshot_bots_see_the_hits_taken_before_their_turn_on_the_next_tick() {
+        let mut battle = battle_with_bots(vec![
+            (Position { x: 0, y: 0 }, Bot::new(BotId(0), Color::Blue)),
+            (Position { x: 0, y: 1 }, Bot::new(BotId(1), Color::Red)),
+        ]);
+        battle.colors.push(ColorConfig {
+            color: Color::Red,
+            number_of_bots: 0,
+            strategy: Strategy::PerBot(idle),
+        });
+        battle.rules.decisions = Decisions::Snapshot;
+        let damage_taken = |battle: &Battle| {
+            battle
+                .state
+                .feedback(BotId(1))
+                .map_or(vec![], |feedback| feedback.damage_taken.clone())
+        };
+
+        // The attacker goes first, after the victim already decided on the snapshot
+        battle.update();
+        let hits = damage_taken(&battle);
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].attacker, BotId(0));
+
+        // The chainsaw points away on the next tick, and the victim's turn clears the hit it saw
+        battle.update();
+        assert!(damage_taken(&battle).is_empty());
+    }
+
+    #[test]
+    fn parallel_snapshot_decisions_match_sequential_ones() {
+        let play = |threads| {
+            let colors = [Color::Blue, Color::Red]
+                .into_iter()
+                .map(|color| ColorConfig {
+                    color,
+                    number_of_bots: 6,
+                    strategy: Strategy::PerBot(wander),
+                })
+                .collect();
+            let rules = Rules {
+                decisions: Decisions::Snapshot,
+                ..Default::default()
+            };
+            let mut battle = Battle::with_seed(colors, rules, 7);
+            battle.threads = threads;
+            for _ in 0..50 {
+                battle.update();
+            }
+
+            format!("{:?}", battle.events)
+        };
+
+        assert_eq!(play(1), play(4));
+    }
 }
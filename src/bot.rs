@@ -1,47 +1,133 @@
+use std::{collections::HashMap, sync::Arc};
+
 use ruscii::terminal::Color;
 
 use super::{
     actuators::Actuators,
     state::{GameState, Position},
     direction::Direction,
+    feedback::AttackOutcome,
+    resource::{Resource, ResourceKind},
+    rules::{Rules, ShieldRules},
+    topology::Topology,
 };
 use super::constants::*;
 
 pub type BotStrategy = fn(&GameState, Position) -> Actuators;
 
+// Decides for all the bots of a team at once, receives the bots of the team
+// and returns the actuators for each of them by their id
+pub type TeamStrategy = fn(&GameState, &[(Position, Bot)]) -> HashMap<BotId, Actuators>;
+
+// Like a bot strategy, with its magic numbers given as a vector of parameters so that they can be tuned
+pub type ParameterizedStrategy = fn(&GameState, Position, &[f64]) -> Actuators;
+
+/// Strategy that needs the rules and the seed of the battle it plays in, like a search that
+/// simulates the next ticks. Shared between the threads that call the strategies
+pub trait SearchStrategy: Send + Sync {
+    fn decide_in_battle(
+        &self,
+        game_state: &GameState,
+        bot_position: Position,
+        rules: &Rules,
+        seed: u64,
+    ) -> Actuators;
+}
+
+#[derive(Clone)]
+pub enum Strategy {
+    PerBot(BotStrategy),
+    Team(TeamStrategy),
+    Parameterized(ParameterizedStrategy, Arc<[f64]>),
+    // Searches each decision with the rules of the battle and a seed derived from the battle's one
+    Search(Arc<dyn SearchStrategy>),
+}
+
+#[derive(Clone)]
 pub struct ColorConfig {
     pub color: Color,
     pub number_of_bots: usize,
-    pub strategy: BotStrategy,
+    pub strategy: Strategy,
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct BotId(pub usize);
+
 #[derive(Clone, Copy, Debug)]
 pub struct Bot {
+    pub id: BotId,
     pub energy: usize,
     pub color: Color,
     pub chainsaw_direction: Direction,
     pub shield_direction: Direction,
     pub tiredness: usize,
     pub shield_resistance: usize,
+    pub ticks_without_damage: usize,
 }
 
 impl Bot {
-    pub fn new(color: Color) -> Bot {
+    pub fn new(id: BotId, color: Color) -> Bot {
         Bot {
+            id,
             energy: BOTS_STARTING_ENERGY,
             color,
             shield_direction: Direction::Up,
             chainsaw_direction: Direction::Left,
             tiredness: 0,
             shield_resistance: STARTING_SHIELD_RESISTANCE,
+            ticks_without_damage: 0,
         }
     }
 
-    pub fn gain_energy(&mut self, energy_gain: usize) {
-        self.energy += energy_gain;
+    // Same as new, but facing directions that are available in the given topology
+    pub fn new_in(id: BotId, color: Color, topology: Topology) -> Bot {
+        let mut bot = Bot::new(id, color);
+        bot.shield_direction = topology.closest_direction(bot.shield_direction);
+        bot.chainsaw_direction = topology.closest_direction(bot.chainsaw_direction);
+
+        bot
+    }
 
-        if self.energy > BOTS_STARTING_ENERGY {
-            self.energy = BOTS_STARTING_ENERGY
+    pub fn gain_energy(&mut self, energy_gain: usize, max_energy: usize) {
+        self.energy = self.energy.saturating_add(energy_gain).min(max_energy);
+    }
+
+    // Returns the energy actually spent
+    pub fn spend_energy(&mut self, amount: usize) -> usize {
+        let spent = amount.min(self.energy);
+        self.energy -= spent;
+
+        spent
+    }
+
+    // Returns the shield resistance actually recovered
+    pub fn repair_shield(&mut self, amount: usize) -> usize {
+        let repaired =
+            amount.min(STARTING_SHIELD_RESISTANCE.saturating_sub(self.shield_resistance));
+        self.shield_resistance += repaired;
+
+        repaired
+    }
+
+    // Called at the end of every tick, regenerates the shield after enough ticks without damage.
+    // The tick the bot took damage in doesn't count as a tick without damage
+    pub fn regenerate_shield(&mut self, rules: &ShieldRules) {
+        if let Some(delay) = rules.regeneration_delay {
+            if self.ticks_without_damage >= delay {
+                self.repair_shield(rules.regeneration_amount);
+            }
+        }
+
+        self.ticks_without_damage = self.ticks_without_damage.saturating_add(1);
+    }
+
+    pub fn collect(&mut self, resource: &Resource, max_energy: usize) {
+        match resource.kind {
+            ResourceKind::Energy => self.gain_energy(resource.amount, max_energy),
+            ResourceKind::ShieldRepair => {
+                self.repair_shield(resource.amount);
+            }
+            ResourceKind::TirednessReset => self.tiredness = 0,
         }
     }
 
@@ -49,28 +135,129 @@ impl Bot {
         self.shield_resistance == 0
     }
 
+    // A bot is destroyed as soon as its energy reaches 0
+    pub fn is_destroyed(&self) -> bool {
+        self.energy == 0
+    }
+
     pub fn move_step(&mut self) {
-        self.tiredness += 1;
+        self.tiredness = self.tiredness.saturating_add(1);
 
         if self.tiredness >= TIREDNESS_TO_LOSE_ENERGY {
             self.tiredness = 0;
-            self.energy -= 1;
+            self.energy = self.energy.saturating_sub(1);
         }
     }
 
-    pub fn receive_attack(&mut self, from_direction: Direction) {
-        if self.shield_direction.opposite().eq(&from_direction) && self.shield_resistance > 0 {
-            if self.shield_resistance > ATTACK_DAMAGE {
-                self.shield_resistance -= ATTACK_DAMAGE;
-            } else {
-                self.shield_resistance = 0;
+    // Bots of the same color are on the same team
+    pub fn is_ally_of(&self, other: &Bot) -> bool {
+        self.color == other.color
+    }
+
+    // The attack travels in from_direction, so it comes from the opposite one
+    pub fn receive_attack(
+        &mut self,
+        from_direction: Direction,
+        damage: usize,
+        topology: Topology,
+    ) -> AttackOutcome {
+        if damage > 0 {
+            self.ticks_without_damage = 0;
+        }
+
+        if topology.shield_covers(self.shield_direction, from_direction.opposite())
+            && self.shield_resistance > 0
+        {
+            let damage = damage.min(self.shield_resistance);
+            self.shield_resistance -= damage;
+
+            AttackOutcome::HitShield {
+                target: self.id,
+                damage,
             }
         } else {
-            if self.energy > ATTACK_DAMAGE {
-                self.energy -= ATTACK_DAMAGE;
-            } else {
-                self.energy = 0;
+            let damage = damage.min(self.energy);
+            self.energy -= damage;
+
+            AttackOutcome::HitEnergy {
+                target: self.id,
+                damage,
             }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn move_step_does_not_underflow_energy() {
+        let mut bot = Bot::new(BotId(0), Color::Blue);
+        bot.energy = 0;
+        bot.tiredness = TIREDNESS_TO_LOSE_ENERGY - 1;
+
+        bot.move_step();
+
+        assert_eq!(bot.energy, 0);
+    }
+
+    #[test]
+    fn gain_energy_saturates_at_max_energy() {
+        let mut bot = Bot::new(BotId(0), Color::Blue);
+
+        bot.gain_energy(usize::MAX, BOTS_STARTING_ENERGY);
+
+        assert_eq!(bot.energy, BOTS_STARTING_ENERGY);
+    }
+
+    #[test]
+    fn receive_attack_saturates_energy_and_shield() {
+        let mut bot = Bot::new(BotId(0), Color::Blue);
+        bot.energy = 1;
+        bot.shield_resistance = 1;
+
+        // The shield is pointing up, so it blocks attacks coming from above
+        bot.receive_attack(Direction::Down, ATTACK_DAMAGE, Topology::Square4);
+        assert_eq!(bot.shield_resistance, 0);
+        assert_eq!(bot.energy, 1);
+
+        bot.receive_attack(Direction::Down, ATTACK_DAMAGE, Topology::Square4);
+        assert_eq!(bot.energy, 0);
+        assert!(bot.is_destroyed());
+    }
+
+    #[test]
+    fn shield_covers_diagonal_attacks_next_to_it() {
+        let mut bot = Bot::new(BotId(0), Color::Blue);
+
+        // Attacks coming from the upper right and upper left corners
+        let outcome = bot.receive_attack(Direction::DownLeft, ATTACK_DAMAGE, Topology::Square8);
+        assert!(matches!(outcome, AttackOutcome::HitShield { .. }));
+        let outcome = bot.receive_attack(Direction::DownRight, ATTACK_DAMAGE, Topology::Square8);
+        assert!(matches!(outcome, AttackOutcome::HitShield { .. }));
+
+        // Attack coming from the right
+        let outcome = bot.receive_attack(Direction::Left, ATTACK_DAMAGE, Topology::Square8);
+        assert!(matches!(outcome, AttackOutcome::HitEnergy { .. }));
+    }
+
+    #[test]
+    fn shield_regenerates_after_the_delay_without_damage() {
+        let rules = ShieldRules {
+            regeneration_delay: Some(1),
+            regeneration_amount: 1,
+            ..Default::default()
+        };
+        let mut bot = Bot::new(BotId(0), Color::Blue);
+        bot.shield_resistance = 0;
+
+        // Hit from below, where the shield doesn't cover
+        bot.receive_attack(Direction::Up, 1, Topology::Square4);
+        bot.regenerate_shield(&rules);
+        assert_eq!(bot.shield_resistance, 0);
+
+        bot.regenerate_shield(&rules);
+        assert_eq!(bot.shield_resistance, 1);
+    }
+}
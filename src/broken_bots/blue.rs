@@ -58,6 +58,7 @@ fn adjacent_position_in_direction(
         Direction::Up => (x, y - 1),
         Direction::Left => (x - 1, y),
         Direction::Right => (x + 1, y),
+        _ => (x, y),
     }
 }
 
@@ -91,25 +92,23 @@ fn rotate_direction(direction: &Direction, rotation: &Rotation) -> Direction {
             Direction::Down => Direction::Left,
             Direction::Right => Direction::Down,
             Direction::Left => Direction::Up,
+            _ => *direction,
         },
         Rotation::Counterclockwise => match direction {
             Direction::Up => Direction::Left,
             Direction::Down => Direction::Right,
             Direction::Right => Direction::Up,
             Direction::Left => Direction::Down,
+            _ => *direction,
         },
     }
 }
 
-/** Lesson 5 */
+/* Lesson 5 */
 
 // Returns a bot if there is one in the given position
 fn bot_in_position(game_state: &GameState, position: &Position) -> Option<Bot> {
-    game_state
-        .bots
-        .iter()
-        .find(|b| b.0.x == position.x && b.0.y == position.y)
-        .map(|(_, b)| b.clone())
+    game_state.bot_at(position).copied()
 }
 
 // Filter out the positions that are not in the bounds of the map
@@ -160,7 +159,7 @@ fn valid_adjacent_positions(
         .map(|(x, y)| Position { x, y })
         .collect();
 
-    filter_valid_positions(positions, game_state.map_width, game_state.map_height)
+    filter_valid_positions(positions, game_state.map_width(), game_state.map_height())
 }
 
 // Returns the direction that the to position is relative to the from position
@@ -192,8 +191,8 @@ fn adjacent_bot(
             is_position_inside_map_bounds(
                 position.x,
                 position.y,
-                game_state.map_width,
-                game_state.map_height,
+                game_state.map_width(),
+                game_state.map_height(),
             )
         })
         .find(|pos| is_bot(game_state, pos));
@@ -211,7 +210,7 @@ fn adjacent_bot(
 // Control which way the shield should rotate
 // If returns None, the shield won't rotate at all
 fn shield_rotation(game_state: &GameState, bot_position: &Position) -> Option<Rotation> {
-    let maybe_bot = bot_in_position(game_state, &bot_position);
+    let maybe_bot = bot_in_position(game_state, bot_position);
 
     if let Some(bot) = maybe_bot {
         if let Some(adjacent_bot_direction) = adjacent_bot(game_state, bot_position) {
@@ -229,7 +228,7 @@ fn shield_rotation(game_state: &GameState, bot_position: &Position) -> Option<Ro
 // Controls which way the chainsaw should rotate
 // If returns None, the chainsaw won't rotate at all
 fn chainsaw_rotation(game_state: &GameState, bot_position: &Position) -> Option<Rotation> {
-    let maybe_bot = bot_in_position(game_state, &bot_position);
+    let maybe_bot = bot_in_position(game_state, bot_position);
 
     if let Some(bot) = maybe_bot {
         if let Some(adjacent_bot_direction) = adjacent_bot(game_state, bot_position) {
@@ -258,7 +257,7 @@ fn get_closest_enemy(
 ) -> Option<Position> {
     let mut closest_enemy: Option<Position> = None;
 
-    for (position, _bot) in game_state.bots.iter() {
+    for (position, _bot) in game_state.bots() {
         if !are_positions_equal(bot_position.x, bot_position.y, position.x, position.y) {
             match closest_enemy {
                 Some(Position {
@@ -302,7 +301,7 @@ fn find_shortest_path(
 ) -> Result<Vec<Position>, String> {
     // BFS
 
-    let mut visited = vec![vec![false; game_state.map_height]; game_state.map_width];
+    let mut visited = vec![vec![false; game_state.map_height()]; game_state.map_width()];
     let mut queue: Vec<(Position, Vec<Position>)> = vec![];
 
     visited[from.x][from.y] = true;
@@ -329,8 +328,8 @@ fn find_shortest_path(
             if is_position_inside_map_bounds(
                 adjacent_pos.x,
                 adjacent_pos.y,
-                game_state.map_width,
-                game_state.map_height,
+                game_state.map_width(),
+                game_state.map_height(),
             ) && !visited[adjacent_pos.x][adjacent_pos.y]
             {
                 visited[adjacent_pos.x][adjacent_pos.y] = true;
@@ -377,5 +376,6 @@ pub fn blue(game_state: &GameState, bot_position: Position) -> Actuators {
         rotate_chainsaw: chainsaw_rotation,
         rotate_shield: shield_rotation,
         move_bot,
+        ..Default::default()
     }
 }
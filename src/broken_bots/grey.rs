@@ -41,11 +41,7 @@ pub fn grey(
 
     // Returns a bot if there is one in the given position
     let bot_in_position = |game_state: &GameState, position: &Position| {
-        game_state
-            .bots
-            .iter()
-            .find(|b| b.0.x == position.x && b.0.y == position.y)
-            .map(|(_, b)| b.clone())
+        game_state.bot_at(position).copied()
     };
 
     // Filter out the positions that are not in the bounds of the map
@@ -89,7 +85,7 @@ pub fn grey(
             .map(|(x, y)| Position { x, y })
             .collect();
 
-        filter_valid_positions(positions, game_state.map_width, game_state.map_height)
+        filter_valid_positions(positions, game_state.map_width(), game_state.map_height())
     };
 
     // Returns the direction that the to position is relative to the from position
@@ -118,8 +114,8 @@ pub fn grey(
                 is_position_inside_map_bounds(
                     position.x,
                     position.y,
-                    game_state.map_width,
-                    game_state.map_height,
+                    game_state.map_width(),
+                    game_state.map_height(),
                 )
             })
             .find(|pos| is_bot(game_state, pos));
@@ -137,7 +133,7 @@ pub fn grey(
     // Control which way the shield should rotate
     // If returns None, the shield won't rotate at all
     let shield_rotation = |game_state: &GameState, bot_position: &Position| {
-        let maybe_bot = bot_in_position(game_state, &bot_position);
+        let maybe_bot = bot_in_position(game_state, bot_position);
 
         if let Some(bot) = maybe_bot {
             if let Some(adjacent_bot_direction) = adjacent_bot(game_state, bot_position) {
@@ -156,7 +152,7 @@ pub fn grey(
     // Controls which way the chainsaw should rotate
     // If returns None, the chainsaw won't rotate at all
     let chainsaw_rotation = |game_state: &GameState, bot_position: &Position| {
-        let maybe_bot = bot_in_position(game_state, &bot_position);
+        let maybe_bot = bot_in_position(game_state, bot_position);
 
         if let Some(bot) = maybe_bot {
             if let Some(adjacent_bot_direction) = adjacent_bot(game_state, bot_position) {
@@ -182,7 +178,7 @@ pub fn grey(
     let get_closest_enemy = |game_state: &GameState, bot_position: &Position| {
         let mut closest_enemy: Option<Position> = None;
 
-        for (position, _bot) in game_state.bots.iter() {
+        for (position, _bot) in game_state.bots() {
             if are_positions_equal(bot_position.x, bot_position.y, position.x, position.y) {
                 match closest_enemy {
                     Some(Position {
@@ -207,7 +203,7 @@ pub fn grey(
         |game_state: &GameState, from: &Position, to: &Position| -> Result<Vec<Position>, String> {
             // BFS
 
-            let mut visited = vec![vec![false; game_state.map_height]; game_state.map_width];
+            let mut visited = vec![vec![false; game_state.map_height()]; game_state.map_width()];
             let mut queue: Vec<(Position, Vec<Position>)> = vec![];
 
             visited[from.x][from.y] = true;
@@ -234,8 +230,8 @@ pub fn grey(
                     if is_position_inside_map_bounds(
                         adjacent_pos.x,
                         adjacent_pos.y,
-                        game_state.map_width,
-                        game_state.map_height,
+                        game_state.map_width(),
+                        game_state.map_height(),
                     ) && !visited[adjacent_pos.x][adjacent_pos.y]
                     {
                         visited[adjacent_pos.x][adjacent_pos.y] = true;
@@ -289,5 +285,6 @@ pub fn grey(
         rotate_chainsaw: chainsaw_rotation,
         rotate_shield: shield_rotation,
         move_bot,
+        ..Default::default()
     }
 }
@@ -1,3 +1,5 @@
+// The broken bots only know the four directions of square maps, their helpers treat any other
+// direction as staying in place or not rotating
 pub mod blue;
 pub mod grey;
 pub mod red;
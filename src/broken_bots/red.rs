@@ -33,19 +33,21 @@ pub fn red(
             Direction::Down => Direction::Left,
             Direction::Right => Direction::Down,
             Direction::Left => Direction::Up,
+            _ => *direction,
         },
         Rotation::Counterclockwise => match direction {
             Direction::Up => Direction::Left,
             Direction::Down => Direction::Right,
             Direction::Right => Direction::Up,
             Direction::Left => Direction::Down,
+            _ => *direction,
         },
     };
 
     // Control which way the shield should rotate
     // If returns None, the shield won't rotate at all
     let shield_rotation = |game_state: &GameState, bot_position: &Position| {
-        let maybe_bot = bot_in_position(game_state, &bot_position);
+        let maybe_bot = bot_in_position(game_state, bot_position);
 
         if let Some(bot) = maybe_bot {
             if let Some(adjacent_bot_direction) = adjacent_bot(game_state, bot_position) {
@@ -64,7 +66,7 @@ pub fn red(
     // Controls which way the chainsaw should rotate
     // If returns None, the chainsaw won't rotate at all
     let chainsaw_rotation = |game_state: &GameState, bot_position: &Position| {
-        let maybe_bot = bot_in_position(game_state, &bot_position);
+        let maybe_bot = bot_in_position(game_state, bot_position);
 
         if let Some(bot) = maybe_bot {
             if let Some(adjacent_bot_direction) = adjacent_bot(game_state, bot_position) {
@@ -90,7 +92,7 @@ pub fn red(
         |game_state: &GameState, from: &Position, to: &Position| -> Result<Vec<Position>, String> {
             // BFS
 
-            let mut visited = vec![vec![false; game_state.map_height]; game_state.map_width];
+            let mut visited = vec![vec![false; game_state.map_height()]; game_state.map_width()];
             let mut queue: Vec<(Position, Vec<Position>)> = vec![];
 
             visited[from.x][from.y] = true;
@@ -117,8 +119,8 @@ pub fn red(
                     if is_position_inside_map_bounds(
                         adjacent_pos.x,
                         adjacent_pos.y,
-                        game_state.map_width,
-                        game_state.map_height,
+                        game_state.map_width(),
+                        game_state.map_height(),
                     ) && !visited[adjacent_pos.x][adjacent_pos.y]
                     {
                         visited[adjacent_pos.x][adjacent_pos.y] = true;
@@ -172,5 +174,6 @@ pub fn red(
         rotate_chainsaw: chainsaw_rotation,
         rotate_shield: shield_rotation,
         move_bot,
+        ..Default::default()
     }
 }
@@ -1,5 +1,6 @@
 use crate::*;
 
+#[allow(clippy::too_many_arguments)]
 pub fn yellow(
     game_state: &GameState,
     bot_position: Position,
@@ -18,6 +19,7 @@ pub fn yellow(
         Direction::Up => (x, y - 1),
         Direction::Left => (x - 1, y),
         Direction::Right => (sum(x, 1), y),
+        _ => (x, y),
     };
 
     // Returns the shortest way to rotate the "from" direction to get the "to" direction
@@ -39,22 +41,20 @@ pub fn yellow(
             Direction::Down => Direction::Left,
             Direction::Right => Direction::Down,
             Direction::Left => Direction::Up,
+            _ => *direction,
         },
         Rotation::Counterclockwise => match direction {
             Direction::Up => Direction::Left,
             Direction::Down => Direction::Right,
             Direction::Right => Direction::Up,
             Direction::Left => Direction::Down,
+            _ => *direction,
         },
     };
 
     // Returns a bot if there is one in the given position
     let bot_in_position = |game_state: &GameState, position: &Position| {
-        game_state
-            .bots
-            .iter()
-            .find(|b| b.0.x == position.x && b.0.y == position.y)
-            .map(|(_, b)| b.clone())
+        game_state.bot_at(position).copied()
     };
 
     // Returns whether there is a bot in the given position
@@ -103,7 +103,7 @@ pub fn yellow(
             .map(|(x, y)| Position { x, y })
             .collect();
 
-        filter_valid_positions(positions, game_state.map_width, game_state.map_height)
+        filter_valid_positions(positions, game_state.map_width(), game_state.map_height())
     };
 
     // Returns the direction that the to position is relative to the from position
@@ -132,8 +132,8 @@ pub fn yellow(
                 is_position_inside_map_bounds(
                     position.x,
                     position.y,
-                    game_state.map_width,
-                    game_state.map_height,
+                    game_state.map_width(),
+                    game_state.map_height(),
                 )
             })
             .find(|pos| is_bot(game_state, pos));
@@ -151,7 +151,7 @@ pub fn yellow(
     // Control which way the shield should rotate
     // If returns None, the shield won't rotate at all
     let shield_rotation = |game_state: &GameState, bot_position: &Position| {
-        let maybe_bot = bot_in_position(game_state, &bot_position);
+        let maybe_bot = bot_in_position(game_state, bot_position);
 
         if let Some(bot) = maybe_bot {
             if let Some(adjacent_bot_direction) = adjacent_bot(game_state, bot_position) {
@@ -170,7 +170,7 @@ pub fn yellow(
     // Controls which way the chainsaw should rotate
     // If returns None, the chainsaw won't rotate at all
     let chainsaw_rotation = |game_state: &GameState, bot_position: &Position| {
-        let maybe_bot = bot_in_position(game_state, &bot_position);
+        let maybe_bot = bot_in_position(game_state, bot_position);
 
         if let Some(bot) = maybe_bot {
             if let Some(adjacent_bot_direction) = adjacent_bot(game_state, bot_position) {
@@ -196,7 +196,7 @@ pub fn yellow(
     let get_closest_enemy = |game_state: &GameState, bot_position: &Position| {
         let mut closest_enemy: Option<Position> = None;
 
-        for (position, _bot) in game_state.bots.iter() {
+        for (position, _bot) in game_state.bots() {
             if !are_positions_equal(bot_position.x, bot_position.y, position.x, position.y) {
                 match closest_enemy {
                     Some(Position {
@@ -227,7 +227,7 @@ pub fn yellow(
         |game_state: &GameState, from: &Position, to: &Position| -> Result<Vec<Position>, String> {
             // BFS
 
-            let mut visited = vec![vec![false; game_state.map_height]; game_state.map_width];
+            let mut visited = vec![vec![false; game_state.map_height()]; game_state.map_width()];
             let mut queue: Vec<(Position, Vec<Position>)> = vec![];
 
             visited[from.x][from.y] = true;
@@ -254,8 +254,8 @@ pub fn yellow(
                     if is_position_inside_map_bounds(
                         adjacent_pos.x,
                         adjacent_pos.y,
-                        game_state.map_width,
-                        game_state.map_height,
+                        game_state.map_width(),
+                        game_state.map_height(),
                     ) && !visited[adjacent_pos.x][adjacent_pos.y]
                     {
                         visited[adjacent_pos.x][adjacent_pos.y] = true;
@@ -309,5 +309,6 @@ pub fn yellow(
         rotate_chainsaw: chainsaw_rotation,
         rotate_shield: shield_rotation,
         move_bot,
+        ..Default::default()
     }
 }
@@ -0,0 +1,382 @@
+use std::ops::Range;
+
+use ruscii::terminal::Color;
+
+use super::{
+    batch::{run_batch, MatchOutcome, MatchSpec},
+    bot::{ColorConfig, Strategy},
+    constants::*,
+    rules::Rules,
+};
+
+// The colors only tell the teams apart, the strategies are what gets compared
+const COMPARISON_COLOR_A: Color = Color::Green;
+const COMPARISON_COLOR_B: Color = Color::Red;
+
+/// Plays strategy A against strategy B over pairs of battles until one is significantly better
+///
+/// Both battles of a pair use the same seed with the teams swapped, so each strategy gets the
+/// other's spawn positions and place in the scan order. Pairs are played in fixed size rounds,
+/// so the results don't depend on the amount of threads.
+///
+/// The score is checked after every round, so each check only gets its share of the error rate
+/// of the confidence intervals (Bonferroni correction). This keeps the chance of stopping on a
+/// difference that isn't there under that error rate, no matter how many rounds are played.
+#[derive(Clone)]
+pub struct Comparison {
+    pub a: Strategy,
+    pub b: Strategy,
+    pub number_of_bots: usize,
+    pub rules: Rules,
+    pub max_ticks: usize,
+    // Seed of the first pair, each pair uses the next one
+    pub first_seed: u64,
+    // Pairs played before checking whether to stop for the first time
+    pub min_pairs: u64,
+    pub max_pairs: u64,
+    // Pairs played between checks
+    pub round_size: u64,
+    // Standard scores the confidence intervals span, 1.96 for 95% confidence.
+    // The score interval checked after each round is wider, see `check_z`
+    pub z: f64,
+    pub threads: usize,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ComparisonResults {
+    // Battles won, drawn and lost by A
+    pub wins: usize,
+    pub draws: usize,
+    pub losses: usize,
+    // Score of A in each pair: a point per battle won and half per battle drawn, divided by two
+    pub pair_scores: Vec<f64>,
+    pub z: f64,
+    // Standard scores the score interval spans, corrected for the amount of checks
+    pub check_z: f64,
+    // Whether the comparison stopped before max_pairs because the result was significant
+    pub stopped_early: bool,
+}
+
+impl Comparison {
+    pub fn new(a: Strategy, b: Strategy) -> Comparison {
+        Comparison {
+            a,
+            b,
+            number_of_bots: COMPARISON_BOTS_PER_TEAM,
+            rules: Rules::default(),
+            max_ticks: COMPARISON_MAX_TICKS,
+            first_seed: 0,
+            min_pairs: COMPARISON_MIN_PAIRS,
+            max_pairs: COMPARISON_MAX_PAIRS,
+            round_size: COMPARISON_ROUND_SIZE,
+            z: COMPARISON_Z,
+            threads: 1,
+        }
+    }
+
+    pub fn run(&self) -> ComparisonResults {
+        let a = ColorConfig {
+            color: COMPARISON_COLOR_A,
+            number_of_bots: self.number_of_bots,
+            strategy: self.a.clone(),
+        };
+        let b = ColorConfig {
+            color: COMPARISON_COLOR_B,
+            number_of_bots: self.number_of_bots,
+            strategy: self.b.clone(),
+        };
+        let spec = |colors| MatchSpec {
+            colors,
+            rules: self.rules.clone(),
+            max_ticks: self.max_ticks,
+        };
+        let (a_first, b_first) = (spec(vec![a.clone(), b.clone()]), spec(vec![b, a]));
+
+        let mut results = ComparisonResults {
+            wins: 0,
+            draws: 0,
+            losses: 0,
+            pair_scores: vec![],
+            z: self.z,
+            check_z: self.check_z(),
+            stopped_early: false,
+        };
+        let last_seed = self.first_seed + self.max_pairs;
+
+        for round in self.rounds() {
+            let first_games = run_batch(&a_first, round.clone(), self.threads).matches;
+            let second_games = run_batch(&b_first, round.clone(), self.threads).matches;
+            for (first, second) in first_games.iter().zip(second_games.iter()) {
+                let score = results.add(first.outcome) + results.add(second.outcome);
+                results.pair_scores.push(score / 2.0);
+            }
+
+            if results.is_significant() {
+                results.stopped_early = round.end < last_seed;
+                break;
+            }
+        }
+
+        results
+    }
+
+    // Seeds of the pairs of each round, the first round gets to the minimum amount of pairs at once
+    pub fn rounds(&self) -> Vec<Range<u64>> {
+        let last_seed = self.first_seed + self.max_pairs;
+        let mut rounds = vec![];
+        let mut next_seed = self.first_seed;
+
+        while next_seed < last_seed {
+            let round_size = match rounds.is_empty() {
+                true => self.round_size.max(self.min_pairs),
+                false => self.round_size,
+            };
+            let round = next_seed..(next_seed + round_size.max(1)).min(last_seed);
+            next_seed = round.end;
+            rounds.push(round);
+        }
+
+        rounds
+    }
+
+    // Standard scores of the checks after each round, so that all of them together are as likely
+    // to find a difference that isn't there as a single interval of z standard scores
+    pub fn check_z(&self) -> f64 {
+        let error_rate = 2.0 * (1.0 - normal_cdf(self.z));
+        let checks = self.rounds().len().max(1) as f64;
+
+        two_sided_z(error_rate / checks)
+    }
+}
+
+impl ComparisonResults {
+    pub fn games(&self) -> usize {
+        self.wins + self.draws + self.losses
+    }
+
+    pub fn pairs(&self) -> usize {
+        self.pair_scores.len()
+    }
+
+    pub fn win_rate(&self) -> f64 {
+        rate(self.wins, self.games())
+    }
+
+    pub fn draw_rate(&self) -> f64 {
+        rate(self.draws, self.games())
+    }
+
+    pub fn loss_rate(&self) -> f64 {
+        rate(self.losses, self.games())
+    }
+
+    pub fn win_interval(&self) -> (f64, f64) {
+        wilson_interval(self.wins, self.games(), self.z)
+    }
+
+    pub fn draw_interval(&self) -> (f64, f64) {
+        wilson_interval(self.draws, self.games(), self.z)
+    }
+
+    pub fn loss_interval(&self) -> (f64, f64) {
+        wilson_interval(self.losses, self.games(), self.z)
+    }
+
+    // Average score of A over the pairs, 0.5 means both strategies are as good
+    pub fn score(&self) -> f64 {
+        if self.pair_scores.is_empty() {
+            return 0.5;
+        }
+
+        self.pair_scores.iter().sum::<f64>() / self.pairs() as f64
+    }
+
+    // Confidence interval of the score, treating each pair as one sample.
+    // It spans check_z standard scores, so that it stays valid after stopping early
+    pub fn score_interval(&self) -> (f64, f64) {
+        let pairs = self.pairs() as f64;
+        if pairs < 2.0 {
+            return (0.0, 1.0);
+        }
+
+        let score = self.score();
+        let variance = self
+            .pair_scores
+            .iter()
+            .map(|pair_score| (pair_score - score).powi(2))
+            .sum::<f64>()
+            / (pairs - 1.0);
+        let margin = self.check_z * (variance / pairs).sqrt();
+
+        ((score - margin).max(0.0), (score + margin).min(1.0))
+    }
+
+    // Whether one of the strategies is better than the other with the confidence of the intervals
+    pub fn is_significant(&self) -> bool {
+        let (low, high) = self.score_interval();
+
+        low > 0.5 || high < 0.5
+    }
+
+    // Counts the outcome of a battle and returns the points A got in it
+    fn add(&mut self, outcome: MatchOutcome) -> f64 {
+        match outcome {
+            MatchOutcome::Win(color) if color == COMPARISON_COLOR_A => {
+                self.wins += 1;
+                1.0
+            }
+            MatchOutcome::Win(_) => {
+                self.losses += 1;
+                0.0
+            }
+            MatchOutcome::Draw => {
+                self.draws += 1;
+                0.5
+            }
+        }
+    }
+}
+
+fn rate(count: usize, total: usize) -> f64 {
+    if total == 0 {
+        return 0.0;
+    }
+
+    count as f64 / total as f64
+}
+
+// Cumulative distribution of the standard normal distribution, with the approximation of the
+// error function from Abramowitz and Stegun (7.1.26), which is off by less than 1.5e-7
+fn normal_cdf(z: f64) -> f64 {
+    let x = z.abs() / std::f64::consts::SQRT_2;
+    let t = 1.0 / (1.0 + 0.3275911 * x);
+    let polynomial = ((((1.061405429 * t - 1.453152027) * t + 1.421413741) * t - 0.284496736) * t
+        + 0.254829592)
+        * t;
+    let erf = 1.0 - polynomial * (-x * x).exp();
+
+    match z >= 0.0 {
+        true => 0.5 * (1.0 + erf),
+        false => 0.5 * (1.0 - erf),
+    }
+}
+
+// Standard scores that leave the given probability out of the interval, on both sides together
+fn two_sided_z(error_rate: f64) -> f64 {
+    let (mut low, mut high) = (0.0, 10.0);
+    for _ in 0..60 {
+        let middle = (low + high) / 2.0;
+        if 2.0 * (1.0 - normal_cdf(middle)) > error_rate {
+            low = middle;
+        } else {
+            high = middle;
+        }
+    }
+
+    (low + high) / 2.0
+}
+
+// Wilson score interval of a proportion, which behaves well with few samples and rates close to 0 or 1
+fn wilson_interval(count: usize, total: usize, z: f64) -> (f64, f64) {
+    if total == 0 {
+        return (0.0, 1.0);
+    }
+
+    let (n, p) = (total as f64, rate(count, total));
+    let denominator = 1.0 + z * z / n;
+    let center = (p + z * z / (2.0 * n)) / denominator;
+    let margin = z * (p * (1.0 - p) / n + z * z / (4.0 * n * n)).sqrt() / denominator;
+
+    ((center - margin).max(0.0), (center + margin).min(1.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    use super::*;
+    use crate::{broken_bots::blue::blue, test_helpers::idle};
+
+    // Runs the stopping rule of the comparison on simulated pairs of equally good strategies,
+    // where each battle is won by either of them at random, and returns how often it stops
+    fn false_positive_rate(comparison: &Comparison, check_z: f64) -> f64 {
+        let mut rng = StdRng::seed_from_u64(0);
+        let runs = 2000;
+        let mut false_positives = 0;
+
+        for _ in 0..runs {
+            let mut results = ComparisonResults {
+                wins: 0,
+                draws: 0,
+                losses: 0,
+                pair_scores: vec![],
+                z: comparison.z,
+                check_z,
+                stopped_early: false,
+            };
+            for round in comparison.rounds() {
+                for _ in round {
+                    let score = (0..2).filter(|_| rng.gen_bool(0.5)).count() as f64;
+                    results.pair_scores.push(score / 2.0);
+                }
+                if results.is_significant() {
+                    false_positives += 1;
+                    break;
+                }
+            }
+        }
+
+        false_positives as f64 / runs as f64
+    }
+
+    #[test]
+    fn equally_good_strategies_are_rarely_found_different() {
+        let comparison = Comparison::new(Strategy::PerBot(idle), Strategy::PerBot(idle));
+        assert_eq!(comparison.rounds().len(), 50);
+
+        assert!(false_positive_rate(&comparison, comparison.check_z()) <= 0.05);
+        // Checking every round with the uncorrected interval stops on noise far too often
+        assert!(false_positive_rate(&comparison, comparison.z) > 0.2);
+    }
+
+    #[test]
+    fn check_z_matches_the_normal_distribution() {
+        assert!((two_sided_z(0.05) - 1.96).abs() < 1e-3);
+        assert!((two_sided_z(0.001) - 3.29).abs() < 1e-2);
+
+        let comparison = Comparison {
+            max_pairs: 10,
+            ..Comparison::new(Strategy::PerBot(idle), Strategy::PerBot(idle))
+        };
+        assert_eq!(comparison.rounds(), vec![0..10]);
+        assert!((comparison.check_z() - comparison.z).abs() < 1e-3);
+    }
+
+    #[test]
+    fn comparison_stops_once_the_result_is_significant() {
+        let comparison = Comparison {
+            number_of_bots: 3,
+            max_pairs: 100,
+            ..Comparison::new(Strategy::PerBot(blue), Strategy::PerBot(idle))
+        };
+
+        let results = comparison.run();
+        assert!(results.stopped_early);
+        assert!(results.score_interval().0 > 0.5);
+        assert_eq!(
+            results,
+            Comparison {
+                threads: 4,
+                ..comparison
+            }
+            .run()
+        );
+    }
+
+    #[test]
+    fn wilson_interval_contains_the_rate() {
+        let (low, high) = wilson_interval(3, 10, 1.96);
+        assert!(low < 0.3 && 0.3 < high);
+        assert_eq!(wilson_interval(0, 10, 1.96).0, 0.0);
+    }
+}
@@ -8,4 +8,38 @@ pub const MAP_WIDTH: usize = 30;
 pub const BOTS_STARTING_ENERGY: usize = 9;
 pub const STARTING_SHIELD_RESISTANCE: usize = 10;
 pub const TIREDNESS_TO_LOSE_ENERGY: usize = 6;
-pub const ATTACK_DAMAGE: usize = 3;
\ No newline at end of file
+pub const ATTACK_DAMAGE: usize = 3;
+pub const MAX_MESSAGE_SIZE: usize = 32;
+pub const SHIELD_REGENERATION_AMOUNT: usize = 1;
+pub const SHIELD_REPAIR_AMOUNT: usize = 3;
+pub const SHIELD_REPAIR_ENERGY_COST: usize = 1;
+pub const RESOURCE_MAX_SHIELD_REPAIR: usize = 6;
+pub const RESOURCE_MIN_SHIELD_REPAIR: usize = 2;
+pub const REPLICATION_ENERGY_THRESHOLD: usize = 6;
+pub const SALVAGE_BOUNTY: usize = 3;
+pub const SUDDEN_DEATH_START_TICK: usize = 200;
+pub const SUDDEN_DEATH_TICKS_PER_RING: usize = 20;
+pub const HAZARD_DAMAGE: usize = 1;
+pub const MCTS_ITERATIONS: usize = 200;
+pub const MCTS_DEPTH: usize = 8;
+pub const MCTS_EXPLORATION: f64 = 1.4;
+pub const MCTS_SHARE_WEIGHT: f64 = 0.8;
+pub const REWARD_SURVIVAL: f64 = 0.01;
+pub const REWARD_DAMAGE: f64 = 0.1;
+pub const REWARD_RESOURCE: f64 = 0.05;
+pub const REWARD_OUTCOME: f64 = 1.0;
+pub const EVOLUTION_BOTS_PER_TEAM: usize = 5;
+pub const EVOLUTION_SEEDS: u64 = 10;
+pub const EVOLUTION_MAX_TICKS: usize = 300;
+pub const EVOLUTION_POPULATION: usize = 20;
+pub const EVOLUTION_GENERATIONS: usize = 10;
+pub const EVOLUTION_TOURNAMENT_SIZE: usize = 3;
+pub const EVOLUTION_CROSSOVER_RATE: f64 = 0.7;
+pub const EVOLUTION_MUTATION_RATE: f64 = 0.2;
+pub const EVOLUTION_MUTATION_STRENGTH: f64 = 0.1;
+pub const COMPARISON_BOTS_PER_TEAM: usize = 5;
+pub const COMPARISON_MAX_TICKS: usize = 300;
+pub const COMPARISON_MIN_PAIRS: u64 = 10;
+pub const COMPARISON_MAX_PAIRS: u64 = 500;
+pub const COMPARISON_ROUND_SIZE: u64 = 10;
+pub const COMPARISON_Z: f64 = 1.96;
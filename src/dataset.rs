@@ -0,0 +1,225 @@
+use std::{
+    fmt::Debug,
+    io::{self, Write},
+};
+
+use super::{
+    actuators::Actuators,
+    batch::{is_over, match_result, MatchResult, MatchSpec},
+    battle::Battle,
+    bot::Bot,
+    environment::Observation,
+    state::Position,
+};
+
+/// A decision of a strategy for one of its bots, with what its team could see when it was made
+#[derive(Clone, Debug)]
+pub struct DecisionRecord {
+    pub tick: usize,
+    // The bot as it was when the decision was made
+    pub bot: Bot,
+    pub position: Position,
+    pub observation: Observation,
+    pub actuators: Actuators,
+}
+
+// Plays a battle writing its decisions as JSON lines after every tick, see `write_decisions`.
+// The last line has the outcome of the battle for each team, "win", "loss" or "draw":
+// {"seed":2,"ticks":20,"outcomes":{"Blue":"win","Red":"loss"}}
+pub fn record_match(
+    spec: &MatchSpec,
+    seed: u64,
+    writer: &mut impl Write,
+) -> io::Result<MatchResult> {
+    let mut battle = Battle::with_seed(spec.colors.clone(), spec.rules.clone(), seed);
+    battle.decision_log = Some(vec![]);
+
+    while !is_over(&battle, spec) {
+        battle.update();
+        write_decisions(&mut battle, writer)?;
+    }
+
+    let result = match_result(&battle, spec, seed);
+    let remaining_teams = battle.remaining_teams();
+    let outcomes: Vec<String> = battle
+        .colors
+        .iter()
+        .map(|color_config| {
+            let outcome = match remaining_teams.as_slice() {
+                [winner] if *winner == color_config.color => "win",
+                _ if !remaining_teams.contains(&color_config.color) => "loss",
+                _ => "draw",
+            };
+
+            format!("\"{:?}\":\"{}\"", color_config.color, outcome)
+        })
+        .collect();
+    writeln!(
+        writer,
+        "{{\"seed\":{},\"ticks\":{},\"outcomes\":{{{}}}}}",
+        seed,
+        result.ticks,
+        outcomes.join(",")
+    )?;
+
+    Ok(result)
+}
+
+// Writes the decisions logged in the battle as JSON lines, one per decision, and clears the log.
+// Observations are sparse, only their non-zero values are written as [plane, x, y, value], with
+// the planes numbered in the order of `Plane`
+pub fn write_decisions(battle: &mut Battle, writer: &mut impl Write) -> io::Result<()> {
+    let Some(decision_log) = battle.decision_log.as_mut() else {
+        return Ok(());
+    };
+
+    for record in decision_log.drain(..) {
+        let observation = &record.observation;
+        let cells_per_plane = observation.map_width * observation.map_height;
+        let values: Vec<String> = observation
+            .planes
+            .iter()
+            .enumerate()
+            .filter(|(_, value)| **value != 0.0)
+            .map(|(index, value)| {
+                let (plane, cell) = (index / cells_per_plane, index % cells_per_plane);
+                let (x, y) = (cell / observation.map_height, cell % observation.map_height);
+
+                format!("[{},{},{},{}]", plane, x, y, value)
+            })
+            .collect();
+
+        writeln!(
+            writer,
+            "{{\"seed\":{},\"tick\":{},\"bot\":{},\"color\":\"{:?}\",\"x\":{},\"y\":{},\"energy\":{},\
+             \"shield\":{},\"observation\":{{\"width\":{},\"height\":{},\"values\":[{}]}},\
+             \"actuators\":{}}}",
+            battle.seed,
+            record.tick,
+            record.bot.id.0,
+            record.bot.color,
+            record.position.x,
+            record.position.y,
+            record.bot.energy,
+            record.bot.shield_resistance,
+            observation.map_width,
+            observation.map_height,
+            values.join(","),
+            actuators_json(&record.actuators),
+        )?;
+    }
+
+    Ok(())
+}
+
+fn actuators_json(actuators: &Actuators) -> String {
+    format!(
+        "{{\"rotate_shield\":{},\"rotate_chainsaw\":{},\"move_bot\":{},\"send_message\":{},\
+         \"repair_shield\":{},\"replicate\":{}}}",
+        debug_or_null(actuators.rotate_shield),
+        debug_or_null(actuators.rotate_chainsaw),
+        debug_or_null(actuators.move_bot),
+        actuators
+            .send_message
+            .as_deref()
+            .map_or("null".to_string(), json_string),
+        actuators.repair_shield,
+        debug_or_null(actuators.replicate),
+    )
+}
+
+// Variant names of the enums are written as strings
+fn debug_or_null(value: Option<impl Debug>) -> String {
+    value.map_or("null".to_string(), |value| format!("\"{:?}\"", value))
+}
+
+fn json_string(content: &str) -> String {
+    let mut escaped = String::from("\"");
+    for character in content.chars() {
+        match character {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            character if character.is_control() => {
+                escaped.push_str(&format!("\\u{:04x}", character as u32))
+            }
+            character => escaped.push(character),
+        }
+    }
+    escaped.push('"');
+
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use ruscii::terminal::Color;
+
+    use super::*;
+    use crate::{
+        batch::play_match,
+        bot::{ColorConfig, Strategy},
+        broken_bots::blue::blue,
+        rules::{Decisions, Rules},
+    };
+
+    fn spec() -> MatchSpec {
+        MatchSpec {
+            colors: vec![
+                ColorConfig {
+                    color: Color::Blue,
+                    number_of_bots: 3,
+                    strategy: Strategy::PerBot(blue),
+                },
+                ColorConfig {
+                    color: Color::Red,
+                    number_of_bots: 3,
+                    strategy: Strategy::PerBot(blue),
+                },
+            ],
+            rules: Rules {
+                decisions: Decisions::Snapshot,
+                ..Default::default()
+            },
+            max_ticks: 20,
+        }
+    }
+
+    #[test]
+    fn every_decision_is_written_followed_by_the_outcome() {
+        let spec = spec();
+        let mut output = vec![];
+        let result = record_match(&spec, 2, &mut output).unwrap();
+        let lines: Vec<&str> = std::str::from_utf8(&output).unwrap().lines().collect();
+
+        // Recording doesn't change how the battle plays out
+        assert_eq!(result, play_match(&spec, 2));
+        assert!(lines[0].starts_with("{\"seed\":2,\"tick\":0,"));
+        assert!(lines[..lines.len() - 1]
+            .iter()
+            .all(|line| line.contains("\"observation\":{\"width\":30,\"height\":10,")));
+        assert_eq!(
+            lines[lines.len() - 1],
+            "{\"seed\":2,\"ticks\":20,\"outcomes\":{\"Blue\":\"draw\",\"Red\":\"draw\"}}"
+        );
+        assert_eq!(json_string("say \"hi\"\n"), "\"say \\\"hi\\\"\\u000a\"");
+    }
+
+    #[test]
+    fn written_decisions_are_taken_out_of_the_log() {
+        let spec = spec();
+        let mut battle = Battle::with_seed(spec.colors, spec.rules, 2);
+        battle.decision_log = Some(vec![]);
+        battle.update();
+        let decisions = battle.decision_log.as_ref().map_or(0, |log| log.len());
+
+        let mut output = vec![];
+        write_decisions(&mut battle, &mut output).unwrap();
+        let lines: Vec<&str> = std::str::from_utf8(&output).unwrap().lines().collect();
+        assert_eq!(lines.len(), decisions);
+        assert_eq!(battle.decision_log.map(|log| log.len()), Some(0));
+
+        // Only the five planes of the cells with bots have values, the rest of the map is left out
+        let values = lines[0].split("\"values\":[").nth(1).unwrap();
+        assert_eq!(values.matches('[').count(), 6 * 5);
+    }
+}
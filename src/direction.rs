@@ -1,53 +1,84 @@
-use super::constants::{MAP_HEIGHT, MAP_WIDTH};
-
 #[derive(Clone, Debug, Copy, PartialEq, Eq)]
 pub enum Direction {
     Up,
     Down,
     Right,
     Left,
+    // Diagonal directions are only available in the square-8 and hex topologies
+    UpRight,
+    UpLeft,
+    DownRight,
+    DownLeft,
 }
 
-#[derive(Clone, Copy, PartialEq, Eq)]
+#[derive(Clone, Debug, Copy, PartialEq, Eq)]
 pub enum Rotation {
     Clockwise,
     Counterclockwise,
 }
 
+// Directions in clockwise order, starting from up
+pub(crate) const CLOCKWISE: [Direction; 8] = [
+    Direction::Up,
+    Direction::UpRight,
+    Direction::Right,
+    Direction::DownRight,
+    Direction::Down,
+    Direction::DownLeft,
+    Direction::Left,
+    Direction::UpLeft,
+];
+
 impl Direction {
-    pub fn compute_position(&self, x: usize, y: usize) -> (usize, usize) {
+    // Change in the x and y coordinates when taking a step in this direction
+    pub fn offset(&self) -> (isize, isize) {
         match self {
-            Direction::Up if y + 1 < MAP_HEIGHT => (x, y + 1),
-            Direction::Down if (y as isize) - 1 >= 0 => (x, y - 1),
-            Direction::Right if x + 1 < MAP_WIDTH => (x + 1, y),
-            Direction::Left if (x as isize) - 1 >= 0 => (x - 1, y),
-            _ => (x, y),
+            Direction::Up => (0, 1),
+            Direction::Down => (0, -1),
+            Direction::Right => (1, 0),
+            Direction::Left => (-1, 0),
+            Direction::UpRight => (1, 1),
+            Direction::UpLeft => (-1, 1),
+            Direction::DownRight => (1, -1),
+            Direction::DownLeft => (-1, -1),
         }
     }
 
+    pub fn is_diagonal(&self) -> bool {
+        let (dx, dy) = self.offset();
+
+        dx != 0 && dy != 0
+    }
+
     pub fn opposite(&self) -> Direction {
-        match self {
-            Direction::Up => Direction::Down,
-            Direction::Down => Direction::Up,
-            Direction::Right => Direction::Left,
-            Direction::Left => Direction::Right,
-        }
+        self.turn(4)
     }
 
+    // Rotates 90 degrees
     pub fn rotate(&self, rotation: Rotation) -> Direction {
         match rotation {
-            Rotation::Clockwise => match self {
-                Direction::Up => Direction::Right,
-                Direction::Down => Direction::Left,
-                Direction::Right => Direction::Down,
-                Direction::Left => Direction::Up,
-            },
-            Rotation::Counterclockwise => match self {
-                Direction::Up => Direction::Left,
-                Direction::Down => Direction::Right,
-                Direction::Right => Direction::Up,
-                Direction::Left => Direction::Down,
-            },
+            Rotation::Clockwise => self.turn(2),
+            Rotation::Counterclockwise => self.turn(6),
         }
     }
+
+    // Rotates 45 degrees
+    pub fn rotate_eighth(&self, rotation: Rotation) -> Direction {
+        match rotation {
+            Rotation::Clockwise => self.turn(1),
+            Rotation::Counterclockwise => self.turn(7),
+        }
+    }
+
+    // Clockwise angle from up, in 45 degree steps
+    fn eighths(&self) -> usize {
+        CLOCKWISE
+            .iter()
+            .position(|direction| direction == self)
+            .unwrap_or(0)
+    }
+
+    fn turn(&self, eighths: usize) -> Direction {
+        CLOCKWISE[(self.eighths() + eighths) % 8]
+    }
 }
@@ -0,0 +1,495 @@
+use std::collections::HashMap;
+
+use ruscii::terminal::Color;
+
+use super::{
+    actuators::Actuators,
+    battle::Battle,
+    bot::{BotId, ColorConfig, Strategy},
+    constants::*,
+    direction::{Direction, Rotation, CLOCKWISE},
+    events::GameEvent,
+    rules::Rules,
+    state::{GameCell, GameState, Position},
+};
+
+/// Features of the observation, each one is a plane with a value for every cell of the map
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Plane {
+    OwnBots,
+    Enemies,
+    Resources,
+    // Energy of the bot in the cell, relative to the maximum energy
+    Energy,
+    // Shield resistance of the bot in the cell, relative to the starting resistance
+    Shield,
+    // Direction the shield of the bot in the cell faces, see `facing`
+    ShieldFacing,
+    ChainsawFacing,
+    Hazards,
+}
+
+impl Plane {
+    pub const COUNT: usize = 8;
+}
+
+/// What the agent sees after each step
+#[derive(Clone, Debug, PartialEq)]
+pub struct Observation {
+    // Plane::COUNT planes of map_width * map_height values, indexed by plane, then column, then row
+    pub planes: Vec<f32>,
+    pub map_width: usize,
+    pub map_height: usize,
+    // Bots of the observing team that are still on the map, in scan order
+    pub bots: Vec<BotId>,
+}
+
+impl Observation {
+    pub fn value(&self, plane: Plane, position: &Position) -> f32 {
+        self.planes[(plane as usize * self.map_width + position.x) * self.map_height + position.y]
+    }
+}
+
+/// Weights of each part of the reward of a step, penalties are subtracted
+#[derive(Clone, Copy, Debug)]
+pub struct RewardShaping {
+    // For each bot of the agent left at the end of the step
+    pub survival: f64,
+    // For each point of damage the agent's bots deal to enemies, on their energy or their shield
+    pub damage_dealt: f64,
+    // For each point of damage enemies deal to the agent's bots
+    pub damage_taken: f64,
+    // For each resource the agent's bots collect
+    pub resources: f64,
+    pub win: f64,
+    pub loss: f64,
+}
+
+impl Default for RewardShaping {
+    fn default() -> Self {
+        RewardShaping {
+            survival: REWARD_SURVIVAL,
+            damage_dealt: REWARD_DAMAGE,
+            damage_taken: REWARD_DAMAGE,
+            resources: REWARD_RESOURCE,
+            win: REWARD_OUTCOME,
+            loss: REWARD_OUTCOME,
+        }
+    }
+}
+
+/// The agent plays the bots of one color, the rest of the colors are played by their strategies
+#[derive(Clone)]
+pub struct EnvironmentConfig {
+    pub agent: Color,
+    pub agent_bots: usize,
+    pub opponents: Vec<ColorConfig>,
+    pub rules: Rules,
+    // The episode ends in a draw if more than one team is left after this many ticks
+    pub max_ticks: usize,
+    pub rewards: RewardShaping,
+}
+
+/// How the episode ended for the agent
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EpisodeOutcome {
+    // The agent is the only team left
+    Win,
+    // The agent has no bots left, even if more than one team is still on the map
+    Loss,
+    // The agent and some other team are still on the map after the last tick
+    Draw,
+}
+
+/// Everything else that happened in a step
+#[derive(Clone, Debug)]
+pub struct StepInfo {
+    // Tick that was played in the step
+    pub tick: usize,
+    pub events: Vec<GameEvent>,
+    // Only when the episode is done
+    pub outcome: Option<EpisodeOutcome>,
+}
+
+/// Headless environment to train agents in, in the usual reset and step fashion
+///
+/// Episodes with the same seed and the same actions always play out the same.
+pub struct Environment {
+    pub config: EnvironmentConfig,
+    battle: Option<Battle>,
+}
+
+// Options of each rotating actuator in an action: not rotating, or rotating either way
+const ROTATIONS: [Option<Rotation>; 3] = [
+    None,
+    Some(Rotation::Clockwise),
+    Some(Rotation::Counterclockwise),
+];
+
+// Actions of a bot: every combination of staying or moving in each direction in clockwise order
+// starting from up, with a rotation of the chainsaw and one of the shield, plus repairing the shield
+pub const ACTIONS: usize = (1 + CLOCKWISE.len()) * ROTATIONS.len() * ROTATIONS.len() + 1;
+
+// Actuators of the given action index, out of range actions stay idle. The shield rotation
+// changes fastest, then the chainsaw one, then the movement, so action 0 is staying idle and
+// the last action repairs the shield.
+// Moving in a direction that the topology of the map doesn't have always fails
+pub fn action_actuators(action: usize) -> Actuators {
+    if action == ACTIONS - 1 {
+        return Actuators {
+            repair_shield: true,
+            ..Default::default()
+        };
+    }
+    if action >= ACTIONS {
+        return Actuators::default();
+    }
+
+    let rotations = ROTATIONS.len();
+    let movement = action / (rotations * rotations);
+
+    Actuators {
+        move_bot: movement.checked_sub(1).map(|index| CLOCKWISE[index]),
+        rotate_chainsaw: ROTATIONS[action / rotations % rotations],
+        rotate_shield: ROTATIONS[action % rotations],
+        ..Default::default()
+    }
+}
+
+impl Environment {
+    pub fn new(config: EnvironmentConfig) -> Environment {
+        Environment {
+            config,
+            battle: None,
+        }
+    }
+
+    // Starts a new episode
+    pub fn reset(&mut self, seed: u64) -> Observation {
+        let mut colors = vec![ColorConfig {
+            color: self.config.agent,
+            number_of_bots: self.config.agent_bots,
+            // Only used for the bots the agent gives no action to
+            strategy: Strategy::PerBot(|_, _| Actuators::default()),
+        }];
+        colors.extend(self.config.opponents.iter().cloned());
+
+        let battle = Battle::with_seed(colors, self.config.rules.clone(), seed);
+        let observation = observe(&battle.state, self.config.agent, &self.config.rules);
+        self.battle = Some(battle);
+
+        observation
+    }
+
+    // Plays a tick with the given action for each bot of the agent, bots without an action stay idle.
+    // Panics if there is no episode going on, call reset first
+    pub fn step(&mut self, actions: &HashMap<BotId, usize>) -> (Observation, f64, bool, StepInfo) {
+        let mut battle = self
+            .battle
+            .take()
+            .expect("reset must be called before the first step");
+        let agent = self.config.agent;
+        let colors_before: HashMap<BotId, Color> = battle
+            .state
+            .bots()
+            .map(|(_, bot)| (bot.id, bot.color))
+            .collect();
+
+        let actuators = actions
+            .iter()
+            .filter(|(bot_id, _)| colors_before.get(bot_id) == Some(&agent))
+            .map(|(bot_id, action)| (*bot_id, action_actuators(*action)))
+            .collect();
+        let tick = battle.tick();
+        let events_before = battle.events.len();
+        battle.update_with(&actuators);
+        let events: Vec<GameEvent> = battle.events[events_before..]
+            .iter()
+            .map(|(_, event)| event.clone())
+            .collect();
+
+        let mut reward = self.reward(&battle.state, &colors_before, &events);
+        let remaining_teams = battle.remaining_teams();
+        let outcome = match remaining_teams.as_slice() {
+            _ if !remaining_teams.contains(&agent) => Some(EpisodeOutcome::Loss),
+            [_] => Some(EpisodeOutcome::Win),
+            _ if battle.tick() >= self.config.max_ticks => Some(EpisodeOutcome::Draw),
+            _ => None,
+        };
+        match outcome {
+            Some(EpisodeOutcome::Win) => reward += self.config.rewards.win,
+            Some(EpisodeOutcome::Loss) => reward -= self.config.rewards.loss,
+            _ => {}
+        }
+
+        let info = StepInfo {
+            tick,
+            events,
+            outcome,
+        };
+        let done = outcome.is_some();
+        let observation = observe(&battle.state, self.config.agent, &self.config.rules);
+        self.battle = Some(battle);
+
+        (observation, reward, done, info)
+    }
+
+    // Computed from the events of the step, the feedback in the state only covers the bots that
+    // got to use their actuators
+    fn reward(
+        &self,
+        state: &GameState,
+        colors_before: &HashMap<BotId, Color>,
+        events: &[GameEvent],
+    ) -> f64 {
+        let rewards = &self.config.rewards;
+        // Bots replicated in the step are not in the colors from before it
+        let mut colors = colors_before.clone();
+        for event in events {
+            if let GameEvent::BotReplicated { child, color, .. } = event {
+                colors.insert(*child, *color);
+            }
+        }
+        let is_agent = |bot_id: &BotId| colors.get(bot_id) == Some(&self.config.agent);
+
+        let survivors = state
+            .bots()
+            .filter(|(_, bot)| bot.color == self.config.agent)
+            .count();
+        let mut reward = rewards.survival * survivors as f64;
+
+        for event in events {
+            match event {
+                GameEvent::BotAttacked {
+                    attacker,
+                    target,
+                    damage,
+                    ..
+                } => {
+                    if is_agent(attacker) && !is_agent(target) {
+                        reward += rewards.damage_dealt * *damage as f64;
+                    } else if !is_agent(attacker) && is_agent(target) {
+                        reward -= rewards.damage_taken * *damage as f64;
+                    }
+                }
+                GameEvent::ResourceCollected { bot, .. } if is_agent(bot) => {
+                    reward += rewards.resources;
+                }
+                _ => {}
+            }
+        }
+
+        reward
+    }
+}
+
+// Features of the state as seen by the team of the given color
+pub(crate) fn observe(state: &GameState, team: Color, rules: &Rules) -> Observation {
+    let (map_width, map_height) = (state.map_width(), state.map_height());
+    let mut planes = vec![0.0; Plane::COUNT * map_width * map_height];
+    let mut set = |plane: Plane, position: &Position, value: f32| {
+        planes[(plane as usize * map_width + position.x) * map_height + position.y] = value;
+    };
+
+    for x in 0..map_width {
+        for y in 0..map_height {
+            let position = Position { x, y };
+            if state.is_hazard(&position) {
+                set(Plane::Hazards, &position, 1.0);
+            }
+
+            match state.cell_at(&position) {
+                Some(GameCell::Bot(bot)) => {
+                    let team = match bot.color == team {
+                        true => Plane::OwnBots,
+                        false => Plane::Enemies,
+                    };
+                    set(team, &position, 1.0);
+                    set(
+                        Plane::Energy,
+                        &position,
+                        bot.energy as f32 / rules.energy.max_energy.max(1) as f32,
+                    );
+                    set(
+                        Plane::Shield,
+                        &position,
+                        bot.shield_resistance as f32 / STARTING_SHIELD_RESISTANCE as f32,
+                    );
+                    set(Plane::ShieldFacing, &position, facing(bot.shield_direction));
+                    set(
+                        Plane::ChainsawFacing,
+                        &position,
+                        facing(bot.chainsaw_direction),
+                    );
+                }
+                Some(GameCell::Resource(_)) => set(Plane::Resources, &position, 1.0),
+                _ => {}
+            }
+        }
+    }
+
+    Observation {
+        planes,
+        map_width,
+        map_height,
+        bots: state
+            .bots()
+            .filter(|(_, bot)| bot.color == team)
+            .map(|(_, bot)| bot.id)
+            .collect(),
+    }
+}
+
+// Eighths of a turn clockwise from up, plus one so that no bot is left at 0
+fn facing(direction: Direction) -> f32 {
+    let eighths = CLOCKWISE.iter().position(|d| *d == direction).unwrap_or(0);
+
+    (eighths + 1) as f32 / CLOCKWISE.len() as f32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        bot::Bot,
+        broken_bots::blue::blue,
+        resource::{Resource, ResourceKind},
+        test_helpers::{idle, state_with_bots},
+    };
+
+    fn environment() -> Environment {
+        Environment::new(EnvironmentConfig {
+            agent: Color::Green,
+            agent_bots: 4,
+            opponents: vec![ColorConfig {
+                color: Color::Blue,
+                number_of_bots: 4,
+                strategy: Strategy::PerBot(blue),
+            }],
+            rules: Rules::default(),
+            max_ticks: 100,
+            rewards: RewardShaping::default(),
+        })
+    }
+
+    #[test]
+    fn episodes_with_the_same_seed_and_actions_are_the_same() {
+        let play = || {
+            let mut environment = environment();
+            let mut observation = environment.reset(11);
+            let mut rewards = vec![];
+
+            for step in 0..100 {
+                let actions = observation
+                    .bots
+                    .iter()
+                    .enumerate()
+                    .map(|(index, bot_id)| (*bot_id, (step + index) % ACTIONS))
+                    .collect();
+                let (next_observation, reward, done, _) = environment.step(&actions);
+                observation = next_observation;
+                rewards.push(reward);
+                if done {
+                    break;
+                }
+            }
+
+            (observation, rewards)
+        };
+
+        assert_eq!(play(), play());
+    }
+
+    #[test]
+    fn every_action_index_maps_to_different_actuators() {
+        let actuators: Vec<String> = (0..ACTIONS)
+            .map(|action| format!("{:?}", action_actuators(action)))
+            .collect();
+
+        for (index, action) in actuators.iter().enumerate() {
+            assert!(!actuators[index + 1..].contains(action));
+        }
+
+        // Bots can move and swing both rotating actuators in a single action
+        let combined = (0..ACTIONS).map(action_actuators).find(|actuators| {
+            actuators.move_bot == Some(Direction::Down)
+                && actuators.rotate_chainsaw == Some(Rotation::Counterclockwise)
+                && actuators.rotate_shield == Some(Rotation::Clockwise)
+        });
+        assert!(combined.is_some());
+        assert!(action_actuators(0).is_idle());
+        assert!(action_actuators(ACTIONS - 1).repair_shield);
+        assert!(action_actuators(ACTIONS).is_idle());
+    }
+
+    #[test]
+    fn rewards_come_from_the_events_of_the_step() {
+        let environment = environment();
+        let state = state_with_bots(vec![
+            (Position { x: 0, y: 0 }, Bot::new(BotId(0), Color::Green)),
+            (Position { x: 0, y: 1 }, Bot::new(BotId(1), Color::Blue)),
+        ]);
+        // The third bot was destroyed in the step, its attack still counts
+        let colors_before = HashMap::from([
+            (BotId(0), Color::Green),
+            (BotId(1), Color::Blue),
+            (BotId(2), Color::Blue),
+        ]);
+        let attack = |attacker, target, damage| GameEvent::BotAttacked {
+            attacker: BotId(attacker),
+            target: BotId(target),
+            damage,
+            to_shield: false,
+        };
+        let events = vec![
+            attack(0, 1, 2),
+            attack(1, 0, 1),
+            attack(2, 0, 1),
+            GameEvent::ResourceCollected {
+                bot: BotId(0),
+                resource: Resource {
+                    kind: ResourceKind::Energy,
+                    amount: 3,
+                    expires_at: None,
+                    claimed_by: None,
+                },
+            },
+            GameEvent::ResourceCollected {
+                bot: BotId(1),
+                resource: Resource {
+                    kind: ResourceKind::Energy,
+                    amount: 3,
+                    expires_at: None,
+                    claimed_by: None,
+                },
+            },
+        ];
+
+        let expected =
+            REWARD_SURVIVAL + REWARD_DAMAGE * 2.0 - REWARD_DAMAGE * 2.0 + REWARD_RESOURCE;
+        let reward = environment.reward(&state, &colors_before, &events);
+        assert!((reward - expected).abs() < 1e-9);
+    }
+
+    #[test]
+    fn an_eliminated_agent_loses_even_if_other_teams_are_left() {
+        let mut environment = Environment::new(EnvironmentConfig {
+            agent_bots: 0,
+            opponents: [Color::Blue, Color::Red]
+                .into_iter()
+                .map(|color| ColorConfig {
+                    color,
+                    number_of_bots: 2,
+                    strategy: Strategy::PerBot(idle),
+                })
+                .collect(),
+            ..environment().config
+        });
+        environment.reset(0);
+
+        let (_, reward, done, info) = environment.step(&HashMap::new());
+        assert!(done);
+        assert_eq!(info.outcome, Some(EpisodeOutcome::Loss));
+        assert_eq!(reward, -REWARD_OUTCOME);
+    }
+}
@@ -0,0 +1,66 @@
+use ruscii::terminal::Color;
+
+use super::{bot::BotId, resource::Resource};
+
+/// Something that happened during a tick, the log of events of a battle makes up its replay
+#[derive(Clone, Debug)]
+pub enum GameEvent {
+    // Logged at the start of every tick
+    TurnOrder {
+        bots: Vec<BotId>,
+    },
+    MessageSent {
+        from: BotId,
+        color: Color,
+        content: String,
+    },
+    MessageDropped {
+        from: BotId,
+        size: usize,
+    },
+    MessageDelivered {
+        from: BotId,
+        to: BotId,
+    },
+    // Chainsaw hits, logged before the target is destroyed if the hit destroys it
+    BotAttacked {
+        attacker: BotId,
+        target: BotId,
+        damage: usize,
+        // Whether the shield of the target took the damage
+        to_shield: bool,
+    },
+    ResourceCollected {
+        bot: BotId,
+        resource: Resource,
+    },
+    BotReplicated {
+        parent: BotId,
+        child: BotId,
+        color: Color,
+    },
+    BotDestroyed {
+        bot: BotId,
+        color: Color,
+        // The bot whose attack destroyed it, None if it ran out of energy by itself
+        killer: Option<BotId>,
+    },
+    EnergySpent {
+        bot: BotId,
+        amount: usize,
+        reason: EnergyCost,
+    },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EnergyCost {
+    Rotation,
+    Attack,
+    Move,
+    Idle,
+    ShieldRepair,
+    // Passive drain that every bot suffers each tick
+    Starvation,
+    // Penalty for ending the tick outside of the shrinking arena
+    Hazard,
+}
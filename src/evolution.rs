@@ -0,0 +1,304 @@
+use std::{fs, io, ops::Range, path::Path, sync::Arc};
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use ruscii::terminal::Color;
+
+use super::{
+    batch::{run_batch, MatchSpec},
+    bot::{ColorConfig, ParameterizedStrategy, Strategy},
+    constants::*,
+    rules::Rules,
+};
+
+/// Tunes the parameters of a strategy with a genetic algorithm
+///
+/// Each candidate is scored by playing seeded headless battles against every opponent in the pool,
+/// so a run with the same configuration always evolves the same parameters.
+#[derive(Clone)]
+pub struct Evolution {
+    pub strategy: ParameterizedStrategy,
+    // Lowest and highest value of each parameter
+    pub bounds: Vec<(f64, f64)>,
+    // Color and amount of bots the candidates play with
+    pub color: Color,
+    pub number_of_bots: usize,
+    // Each candidate plays one battle against each of these for every seed
+    pub opponents: Vec<ColorConfig>,
+    pub seeds: Range<u64>,
+    pub rules: Rules,
+    pub max_ticks: usize,
+    pub population: usize,
+    pub generations: usize,
+    // Candidates that compete to be picked as each parent
+    pub tournament_size: usize,
+    // Chance of the children getting each parameter from either parent instead of copying the first one
+    pub crossover_rate: f64,
+    // Chance of each parameter of the children being changed, by up to this strength times its range
+    pub mutation_rate: f64,
+    pub mutation_strength: f64,
+    // Best candidates that are copied unchanged into the next generation
+    pub elitism: usize,
+    // Seeds the random choices of the algorithm
+    pub seed: u64,
+    pub threads: usize,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Candidate {
+    pub parameters: Vec<f64>,
+    // Battles won plus half the battles drawn, divided by the battles played
+    pub fitness: f64,
+}
+
+#[derive(Clone, Debug)]
+pub struct EvolutionResult {
+    pub best: Candidate,
+    // Fitness of the best candidate of each generation
+    pub best_fitness: Vec<f64>,
+}
+
+impl Evolution {
+    pub fn new(
+        strategy: ParameterizedStrategy,
+        bounds: Vec<(f64, f64)>,
+        opponents: Vec<ColorConfig>,
+    ) -> Evolution {
+        Evolution {
+            strategy,
+            bounds,
+            color: Color::Green,
+            number_of_bots: EVOLUTION_BOTS_PER_TEAM,
+            opponents,
+            seeds: 0..EVOLUTION_SEEDS,
+            rules: Rules::default(),
+            max_ticks: EVOLUTION_MAX_TICKS,
+            population: EVOLUTION_POPULATION,
+            generations: EVOLUTION_GENERATIONS,
+            tournament_size: EVOLUTION_TOURNAMENT_SIZE,
+            crossover_rate: EVOLUTION_CROSSOVER_RATE,
+            mutation_rate: EVOLUTION_MUTATION_RATE,
+            mutation_strength: EVOLUTION_MUTATION_STRENGTH,
+            elitism: 1,
+            seed: 0,
+            threads: 1,
+        }
+    }
+
+    pub fn run(&self) -> EvolutionResult {
+        let mut rng = StdRng::seed_from_u64(self.seed);
+        let mut population: Vec<Candidate> = (0..self.population.max(1))
+            .map(|_| {
+                let parameters = self
+                    .bounds
+                    .iter()
+                    .map(|(min, max)| random_in(*min, *max, &mut rng))
+                    .collect();
+                self.evaluate(parameters)
+            })
+            .collect();
+        let mut best_fitness = vec![];
+
+        for generation in 0..self.generations.max(1) {
+            population.sort_by(|a, b| b.fitness.total_cmp(&a.fitness));
+            best_fitness.push(population[0].fitness);
+            if generation + 1 == self.generations.max(1) {
+                break;
+            }
+
+            let mut next_population: Vec<Candidate> =
+                population.iter().take(self.elitism).cloned().collect();
+            while next_population.len() < population.len() {
+                let first = self.tournament(&population, &mut rng);
+                let second = self.tournament(&population, &mut rng);
+                let mut parameters = self.crossover(first, second, &mut rng);
+                self.mutate(&mut parameters, &mut rng);
+
+                next_population.push(self.evaluate(parameters));
+            }
+            population = next_population;
+        }
+
+        EvolutionResult {
+            best: population.swap_remove(0),
+            best_fitness,
+        }
+    }
+
+    // Plays the given parameters against every opponent
+    pub fn evaluate(&self, parameters: Vec<f64>) -> Candidate {
+        let strategy = Strategy::Parameterized(self.strategy, Arc::from(parameters.as_slice()));
+        let mut score = 0.0;
+
+        for opponent in self.opponents.iter() {
+            let spec = MatchSpec {
+                colors: vec![
+                    ColorConfig {
+                        color: self.color,
+                        number_of_bots: self.number_of_bots,
+                        strategy: strategy.clone(),
+                    },
+                    opponent.clone(),
+                ],
+                rules: self.rules.clone(),
+                max_ticks: self.max_ticks,
+            };
+            let results = run_batch(&spec, self.seeds.clone(), self.threads);
+            score += results.win_rate(self.color) + results.draw_rate() / 2.0;
+        }
+
+        Candidate {
+            parameters,
+            fitness: score / self.opponents.len().max(1) as f64,
+        }
+    }
+
+    fn tournament<'a>(&self, population: &'a [Candidate], rng: &mut StdRng) -> &'a Candidate {
+        (0..self.tournament_size.max(1))
+            .map(|_| &population[rng.gen_range(0..population.len())])
+            .max_by(|a, b| a.fitness.total_cmp(&b.fitness))
+            .unwrap_or(&population[0])
+    }
+
+    // Uniform crossover, each parameter comes from either parent
+    fn crossover(&self, first: &Candidate, second: &Candidate, rng: &mut StdRng) -> Vec<f64> {
+        if !rng.gen_bool(self.crossover_rate.clamp(0.0, 1.0)) {
+            return first.parameters.clone();
+        }
+
+        first
+            .parameters
+            .iter()
+            .zip(second.parameters.iter())
+            .map(|(a, b)| if rng.gen_bool(0.5) { *a } else { *b })
+            .collect()
+    }
+
+    fn mutate(&self, parameters: &mut [f64], rng: &mut StdRng) {
+        for (parameter, (min, max)) in parameters.iter_mut().zip(self.bounds.iter()) {
+            if rng.gen_bool(self.mutation_rate.clamp(0.0, 1.0)) {
+                let change = (max - min) * self.mutation_strength;
+                *parameter = (*parameter + random_in(-change, change, rng)).clamp(*min, *max);
+            }
+        }
+    }
+}
+
+// Writes the parameters of the candidate in a single line, after a comment with its fitness
+pub fn write_parameters(path: impl AsRef<Path>, candidate: &Candidate) -> io::Result<()> {
+    let parameters: Vec<String> = candidate
+        .parameters
+        .iter()
+        .map(|parameter| parameter.to_string())
+        .collect();
+
+    fs::write(
+        path,
+        format!(
+            "# fitness {}\n{}\n",
+            candidate.fitness,
+            parameters.join(",")
+        ),
+    )
+}
+
+// Reads the parameters written by write_parameters
+pub fn read_parameters(path: impl AsRef<Path>) -> io::Result<Vec<f64>> {
+    let content = fs::read_to_string(path)?;
+    let line = content
+        .lines()
+        .find(|line| !line.starts_with('#') && !line.trim().is_empty())
+        .unwrap_or("");
+
+    line.split(',')
+        .filter(|value| !value.trim().is_empty())
+        .map(|value| {
+            value
+                .trim()
+                .parse()
+                .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))
+        })
+        .collect()
+}
+
+fn random_in(min: f64, max: f64, rng: &mut StdRng) -> f64 {
+    if min < max {
+        rng.gen_range(min..=max)
+    } else {
+        min
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        actuators::Actuators,
+        broken_bots::blue::blue,
+        direction::{Direction, Rotation},
+        state::{GameState, Position},
+    };
+
+    // Moves towards the given column and swings the chainsaw
+    fn go_to_column(
+        _game_state: &GameState,
+        bot_position: Position,
+        parameters: &[f64],
+    ) -> Actuators {
+        let column = parameters[0] as usize;
+
+        Actuators {
+            rotate_chainsaw: Some(Rotation::Clockwise),
+            move_bot: match bot_position.x.cmp(&column) {
+                std::cmp::Ordering::Less => Some(Direction::Right),
+                std::cmp::Ordering::Greater => Some(Direction::Left),
+                std::cmp::Ordering::Equal => None,
+            },
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn evolution_is_deterministic_and_keeps_the_best_candidate() {
+        let opponent = ColorConfig {
+            color: Color::Blue,
+            number_of_bots: 3,
+            strategy: Strategy::PerBot(blue),
+        };
+        let evolution = Evolution {
+            number_of_bots: 3,
+            seeds: 0..2,
+            max_ticks: 30,
+            population: 4,
+            generations: 3,
+            ..Evolution::new(
+                go_to_column,
+                vec![(0.0, MAP_WIDTH as f64 - 1.0)],
+                vec![opponent],
+            )
+        };
+
+        let result = evolution.run();
+        assert_eq!(result.best, evolution.run().best);
+        assert!(result
+            .best_fitness
+            .windows(2)
+            .all(|pair| pair[0] <= pair[1]));
+        assert_eq!(
+            result.best,
+            evolution.evaluate(result.best.parameters.clone())
+        );
+    }
+
+    #[test]
+    fn parameters_can_be_read_back() {
+        let path = std::env::temp_dir().join("battle_bots_parameters_test.txt");
+        let candidate = Candidate {
+            parameters: vec![0.5, -2.0, 13.25],
+            fitness: 0.75,
+        };
+
+        write_parameters(&path, &candidate).unwrap();
+        assert_eq!(read_parameters(&path).unwrap(), candidate.parameters);
+        fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,48 @@
+use super::{bot::BotId, direction::Direction, resource::Resource};
+
+/// What happened to the last actuators of a bot, and what happened to it since then
+#[derive(Clone, Debug, Default)]
+pub struct ActionFeedback {
+    pub movement: Option<MoveOutcome>,
+    pub attack: Option<AttackOutcome>,
+    pub collected: Option<Resource>,
+    // Shield resistance recovered with the repair actuator
+    pub shield_repaired: Option<usize>,
+    pub replication: Option<ReplicationOutcome>,
+    pub damage_taken: Vec<DamageTaken>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MoveOutcome {
+    Moved,
+    BlockedByBot(BotId),
+    BlockedByMapEdge,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AttackOutcome {
+    HitEnergy { target: BotId, damage: usize },
+    HitShield { target: BotId, damage: usize },
+    // The target was an ally and friendly fire is off
+    AllySpared { target: BotId },
+    Air,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReplicationOutcome {
+    Replicated(BotId),
+    NotEnoughEnergy,
+    // The target position is not empty or is outside the map
+    Blocked,
+    // Replication is disabled in the rules of the battle
+    NotAllowed,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DamageTaken {
+    pub attacker: BotId,
+    // Where the attacker is, relative to the attacked bot
+    pub from_direction: Direction,
+    pub damage: usize,
+    pub to_shield: bool,
+}
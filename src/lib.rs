@@ -1,18 +1,57 @@
 mod actuators;
 mod bot;
 mod direction;
+mod environment;
+mod events;
+mod evolution;
+mod feedback;
+mod message;
 mod resource;
+mod rules;
+mod simulation;
+mod spawn;
 mod state;
+mod topology;
 
+mod batch;
 mod battle;
+mod comparison;
+// The broken bots are lesson exercises, written the way a beginner would write them
+#[allow(clippy::needless_return)]
 mod broken_bots;
 mod constants;
+mod dataset;
+mod mcts;
+#[cfg(test)]
+mod test_helpers;
 
 pub use actuators::Actuators;
+pub use batch::{play_match, run_batch, BatchResults, MatchOutcome, MatchResult, MatchSpec};
 pub use battle::Battle;
-pub use bot::{Bot, BotStrategy, ColorConfig};
+pub use bot::{
+    Bot, BotId, BotStrategy, ColorConfig, ParameterizedStrategy, SearchStrategy, Strategy,
+    TeamStrategy,
+};
 pub use broken_bots::{blue::blue, grey::grey, red::red, yellow::yellow};
+pub use comparison::{Comparison, ComparisonResults};
+pub use dataset::{record_match, write_decisions, DecisionRecord};
 pub use direction::{Direction, Rotation};
-pub use resource::Resource;
+pub use environment::{
+    action_actuators, Environment, EnvironmentConfig, EpisodeOutcome, Observation, Plane,
+    RewardShaping, StepInfo, ACTIONS,
+};
+pub use events::{EnergyCost, GameEvent};
+pub use evolution::{read_parameters, write_parameters, Candidate, Evolution, EvolutionResult};
+pub use feedback::{ActionFeedback, AttackOutcome, DamageTaken, MoveOutcome, ReplicationOutcome};
+pub use mcts::{mcts, random_rollout, Mcts, RolloutPolicy};
+pub use message::Message;
+pub use resource::{Resource, ResourceKind};
+pub use rules::{
+    Decisions, EnergyRules, FriendlyFire, HazardPenalty, Initiative, MessageRules, RepairCost,
+    ReplicationRules, ResourceRules, Rules, SalvageRules, SalvageValue, ShieldRules, SpawnPolicy,
+    SuddenDeathRules,
+};
 pub use ruscii::terminal::Color;
-pub use state::{GameState, Position};
+pub use simulation::simulate;
+pub use state::{GameCell, GameState, MapEdges, Position};
+pub use topology::Topology;
@@ -0,0 +1,358 @@
+use std::{
+    cmp::Reverse,
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+use rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
+use ruscii::terminal::Color;
+
+use super::{
+    actuators::Actuators,
+    bot::{BotId, SearchStrategy},
+    constants::*,
+    direction::Rotation,
+    rules::Rules,
+    simulation::simulate,
+    state::{GameCell, GameState, Position},
+};
+
+// Decides for the bots in the simulated ticks, except for the searching bot while it's in the tree
+pub type RolloutPolicy = fn(&GameState, Position, &mut StdRng) -> Actuators;
+
+/// Monte Carlo tree search over the actions of a single bot, playing the ticks with `simulate`
+///
+/// Every other bot is played by the rollout policy. The search is seeded, so the same state
+/// always gets the same decision unless the time budget cuts the search short.
+#[derive(Clone, Debug)]
+pub struct Mcts {
+    pub iterations: usize,
+    // Stops the search early when it runs out, at least one iteration is always played
+    pub time_budget: Option<Duration>,
+    // Ticks played in each iteration, counting the ones explored by the tree
+    pub depth: usize,
+    // How much the search favours trying actions with few visits over the best ones so far
+    pub exploration: f64,
+    pub rollout: RolloutPolicy,
+    // Rules the ticks are simulated with, and seed of the search.
+    // As a `Strategy::Search`, the battle replaces the rules and mixes its seed into this one
+    pub rules: Rules,
+    pub seed: u64,
+}
+
+impl Default for Mcts {
+    fn default() -> Self {
+        Mcts {
+            iterations: MCTS_ITERATIONS,
+            time_budget: None,
+            depth: MCTS_DEPTH,
+            exploration: MCTS_EXPLORATION,
+            rollout: random_rollout,
+            rules: Rules::default(),
+            seed: 0,
+        }
+    }
+}
+
+#[derive(Default)]
+struct Node {
+    visits: usize,
+    total_value: f64,
+    // Indexed like the actions of the search, empty until the node is expanded
+    children: Vec<Node>,
+}
+
+impl Mcts {
+    pub fn decide(&self, game_state: &GameState, bot_position: Position) -> Actuators {
+        let Some(bot) = game_state.bot_at(&bot_position).copied() else {
+            return Actuators::default();
+        };
+        let actions = candidate_actions(game_state, &bot_position);
+        let mut rng =
+            StdRng::seed_from_u64(self.seed ^ ((game_state.tick() as u64) << 32) ^ bot.id.0 as u64);
+        let deadline = self.time_budget.map(|budget| Instant::now() + budget);
+
+        let mut root = Node::default();
+        for iteration in 0..self.iterations.max(1) {
+            if iteration > 0 && deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+                break;
+            }
+            self.search(
+                &mut root,
+                game_state.clone(),
+                0,
+                bot.id,
+                bot.color,
+                &actions,
+                &mut rng,
+            );
+        }
+
+        // The most visited action is the most robust one, its value has the least noise
+        root.children
+            .iter()
+            .enumerate()
+            .max_by_key(|(index, child)| (child.visits, Reverse(*index)))
+            .map(|(index, _)| actions[index].clone())
+            .unwrap_or_default()
+    }
+
+    // Plays one tick with the action selected for the bot, and returns the value of the outcome
+    #[allow(clippy::too_many_arguments)]
+    fn search(
+        &self,
+        node: &mut Node,
+        state: GameState,
+        depth: usize,
+        bot_id: BotId,
+        color: Color,
+        actions: &[Actuators],
+        rng: &mut StdRng,
+    ) -> f64 {
+        if depth >= self.depth || state.bot(bot_id).is_none() {
+            return evaluate(&state, bot_id, color);
+        }
+
+        if node.children.is_empty() {
+            node.children = actions.iter().map(|_| Node::default()).collect();
+        }
+        let unvisited: Vec<usize> = (0..actions.len())
+            .filter(|index| node.children[*index].visits == 0)
+            .collect();
+        let index = match unvisited.choose(rng) {
+            Some(index) => *index,
+            None => self.best_child(node),
+        };
+
+        let next_state = self.play_tick(&state, Some((bot_id, &actions[index])), rng);
+        let child = &mut node.children[index];
+        let value = if child.visits == 0 {
+            self.rollout(next_state, depth + 1, bot_id, color, rng)
+        } else {
+            self.search(child, next_state, depth + 1, bot_id, color, actions, rng)
+        };
+
+        child.visits += 1;
+        child.total_value += value;
+        node.visits += 1;
+
+        value
+    }
+
+    // Upper confidence bound of each child, all of them have been visited already
+    fn best_child(&self, node: &Node) -> usize {
+        let parent_visits = (node.visits.max(1) as f64).ln();
+        let bound = |child: &Node| {
+            let visits = child.visits as f64;
+            child.total_value / visits + self.exploration * (parent_visits / visits).sqrt()
+        };
+
+        (0..node.children.len())
+            .max_by(|a, b| bound(&node.children[*a]).total_cmp(&bound(&node.children[*b])))
+            .unwrap_or(0)
+    }
+
+    fn rollout(
+        &self,
+        mut state: GameState,
+        depth: usize,
+        bot_id: BotId,
+        color: Color,
+        rng: &mut StdRng,
+    ) -> f64 {
+        for _ in depth..self.depth {
+            if state.bot(bot_id).is_none() {
+                break;
+            }
+            state = self.play_tick(&state, None, rng);
+        }
+
+        evaluate(&state, bot_id, color)
+    }
+
+    // Every bot is played by the rollout policy, except the searching one if its action is given
+    fn play_tick(
+        &self,
+        state: &GameState,
+        action: Option<(BotId, &Actuators)>,
+        rng: &mut StdRng,
+    ) -> GameState {
+        let mut actuators: HashMap<BotId, Actuators> = state
+            .bots()
+            .map(|(position, bot)| (bot.id, (self.rollout)(state, position, rng)))
+            .collect();
+        if let Some((bot_id, action)) = action {
+            actuators.insert(bot_id, action.clone());
+        }
+
+        simulate(state, &actuators, &self.rules, rng).0
+    }
+}
+
+// Searches with the rules of the battle, mixing the battle's seed into the one of the search
+impl SearchStrategy for Mcts {
+    fn decide_in_battle(
+        &self,
+        game_state: &GameState,
+        bot_position: Position,
+        rules: &Rules,
+        seed: u64,
+    ) -> Actuators {
+        let search = Mcts {
+            rules: rules.clone(),
+            seed: self.seed ^ seed,
+            ..self.clone()
+        };
+
+        search.decide(game_state, bot_position)
+    }
+}
+
+/// Searches with the default configuration and rules, to be used as a `Strategy::PerBot`.
+/// Use `Strategy::Search` to configure the search or to play with other rules
+pub fn mcts(game_state: &GameState, bot_position: Position) -> Actuators {
+    Mcts::default().decide(game_state, bot_position)
+}
+
+/// Picks one of the sensible actions of the bot at random
+pub fn random_rollout(
+    game_state: &GameState,
+    bot_position: Position,
+    rng: &mut StdRng,
+) -> Actuators {
+    candidate_actions(game_state, &bot_position)
+        .choose(rng)
+        .cloned()
+        .unwrap_or_default()
+}
+
+// Moving to any free adjacent cell or staying, with or without swinging the chainsaw,
+// or just turning the shield
+fn candidate_actions(game_state: &GameState, bot_position: &Position) -> Vec<Actuators> {
+    let mut moves = vec![None];
+    for (direction, position) in game_state.adjacent_positions(bot_position) {
+        if !matches!(game_state.cell_at(&position), Some(GameCell::Bot(_))) {
+            moves.push(Some(direction));
+        }
+    }
+
+    let chainsaw_rotations = [
+        None,
+        Some(Rotation::Clockwise),
+        Some(Rotation::Counterclockwise),
+    ];
+    let mut actions: Vec<Actuators> = moves
+        .into_iter()
+        .flat_map(|move_bot| {
+            chainsaw_rotations.map(|rotate_chainsaw| Actuators {
+                move_bot,
+                rotate_chainsaw,
+                ..Default::default()
+            })
+        })
+        .collect();
+    for rotation in [Rotation::Clockwise, Rotation::Counterclockwise] {
+        actions.push(Actuators {
+            rotate_shield: Some(rotation),
+            ..Default::default()
+        });
+    }
+
+    actions
+}
+
+// Share of the energy on the map that belongs to the team, with a bonus for the bot staying alive
+fn evaluate(state: &GameState, bot_id: BotId, color: Color) -> f64 {
+    let (mut team_energy, mut total_energy) = (0, 0);
+    for (_, bot) in state.bots() {
+        if bot.color == color {
+            team_energy += bot.energy;
+        }
+        total_energy += bot.energy;
+    }
+    let share = match total_energy {
+        0 => 0.5,
+        _ => team_energy as f64 / total_energy as f64,
+    };
+    let alive = match state.bot(bot_id) {
+        Some(_) => 1.0,
+        None => 0.0,
+    };
+
+    MCTS_SHARE_WEIGHT * share + (1.0 - MCTS_SHARE_WEIGHT) * alive
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::{
+        battle::Battle,
+        bot::{ColorConfig, Strategy},
+        rules::{Decisions, EnergyRules},
+    };
+
+    #[test]
+    fn searches_with_the_same_seed_decide_the_same() {
+        let colors = [Color::Blue, Color::Red]
+            .into_iter()
+            .map(|color| ColorConfig {
+                color,
+                number_of_bots: 4,
+                strategy: Strategy::PerBot(mcts),
+            })
+            .collect();
+        let battle = Battle::with_seed(colors, Rules::default(), 5);
+        let search = Mcts {
+            iterations: 50,
+            ..Default::default()
+        };
+
+        for (position, _) in battle.state.bots() {
+            let first = search.decide(&battle.state, position.clone());
+            let second = search.decide(&battle.state, position);
+            assert_eq!(format!("{:?}", first), format!("{:?}", second));
+        }
+    }
+
+    #[test]
+    fn search_strategies_use_the_rules_and_the_seed_of_the_battle() {
+        let search = Mcts {
+            iterations: 20,
+            seed: 3,
+            ..Default::default()
+        };
+        let colors = [Color::Blue, Color::Red]
+            .into_iter()
+            .map(|color| ColorConfig {
+                color,
+                number_of_bots: 2,
+                strategy: Strategy::Search(Arc::new(search.clone())),
+            })
+            .collect();
+        // Every bot decides on the same state, the one at the start of the tick
+        let rules = Rules {
+            energy: EnergyRules {
+                move_cost: 2,
+                ..Default::default()
+            },
+            decisions: Decisions::Snapshot,
+            ..Default::default()
+        };
+        let mut battle = Battle::with_seed(colors, rules.clone(), 5);
+        battle.decision_log = Some(vec![]);
+        let state = battle.state.clone();
+        battle.update();
+
+        let battle_search = Mcts {
+            rules,
+            seed: 3 ^ 5,
+            ..search
+        };
+        for record in battle.decision_log.unwrap() {
+            let expected = battle_search.decide(&state, record.position);
+            assert_eq!(format!("{:?}", record.actuators), format!("{:?}", expected));
+        }
+    }
+}
@@ -0,0 +1,179 @@
+use std::collections::HashMap;
+
+use ruscii::terminal::Color;
+
+use super::{
+    bot::{Bot, BotId},
+    events::GameEvent,
+    rules::MessageRules,
+    state::Position,
+};
+
+#[derive(Clone, Debug)]
+pub struct Message {
+    pub from: BotId,
+    pub color: Color,
+    // Position of the sender when the message was sent
+    pub position: Position,
+    pub sent_at: usize,
+    pub content: String,
+}
+
+#[derive(Clone, Debug, Default)]
+pub(crate) struct MessageBus {
+    in_flight: Vec<(usize, Message)>,
+    inboxes: HashMap<BotId, Vec<Message>>,
+}
+
+impl MessageBus {
+    pub fn inbox(&self, bot_id: BotId) -> &[Message] {
+        self.inboxes
+            .get(&bot_id)
+            .map(|messages| messages.as_slice())
+            .unwrap_or(&[])
+    }
+
+    pub fn post(&mut self, message: Message, rules: &MessageRules) -> GameEvent {
+        if message.content.len() > rules.max_size {
+            return GameEvent::MessageDropped {
+                from: message.from,
+                size: message.content.len(),
+            };
+        }
+
+        let deliver_at = message.sent_at + 1 + rules.delay;
+        let event = GameEvent::MessageSent {
+            from: message.from,
+            color: message.color,
+            content: message.content.clone(),
+        };
+        self.in_flight.push((deliver_at, message));
+
+        event
+    }
+
+    // Replaces the inboxes with the messages due at the given tick
+    pub fn deliver(
+        &mut self,
+        tick: usize,
+        bots: &[(Position, Bot)],
+        rules: &MessageRules,
+        distance: impl Fn(&Position, &Position) -> usize,
+    ) -> Vec<GameEvent> {
+        self.inboxes.clear();
+
+        let (due, in_flight) = std::mem::take(&mut self.in_flight)
+            .into_iter()
+            .partition(|(deliver_at, _)| *deliver_at <= tick);
+        self.in_flight = in_flight;
+
+        let mut events = vec![];
+
+        for (_, message) in due {
+            for (position, bot) in bots {
+                if bot.id == message.from || bot.color != message.color {
+                    continue;
+                }
+                if let Some(range) = rules.range {
+                    if distance(&message.position, position) > range {
+                        continue;
+                    }
+                }
+
+                self.inboxes
+                    .entry(bot.id)
+                    .or_default()
+                    .push(message.clone());
+                events.push(GameEvent::MessageDelivered {
+                    from: message.from,
+                    to: bot.id,
+                });
+            }
+        }
+
+        events
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(content: &str) -> Message {
+        Message {
+            from: BotId(0),
+            color: Color::Blue,
+            position: Position { x: 0, y: 0 },
+            sent_at: 0,
+            content: content.to_string(),
+        }
+    }
+
+    fn bots() -> Vec<(Position, Bot)> {
+        vec![
+            (Position { x: 0, y: 0 }, Bot::new(BotId(0), Color::Blue)),
+            (Position { x: 1, y: 0 }, Bot::new(BotId(1), Color::Blue)),
+            (Position { x: 5, y: 0 }, Bot::new(BotId(2), Color::Blue)),
+            (Position { x: 0, y: 1 }, Bot::new(BotId(3), Color::Red)),
+        ]
+    }
+
+    fn manhattan(from: &Position, to: &Position) -> usize {
+        from.x.abs_diff(to.x) + from.y.abs_diff(to.y)
+    }
+
+    #[test]
+    fn messages_over_the_max_size_are_dropped() {
+        let rules = MessageRules {
+            max_size: 4,
+            ..Default::default()
+        };
+        let mut bus = MessageBus::default();
+
+        assert!(matches!(
+            bus.post(message("hello"), &rules),
+            GameEvent::MessageDropped { size: 5, .. }
+        ));
+        assert!(matches!(
+            bus.post(message("hey"), &rules),
+            GameEvent::MessageSent { .. }
+        ));
+        bus.deliver(1, &bots(), &rules, manhattan);
+        assert_eq!(bus.inbox(BotId(1)).len(), 1);
+        assert_eq!(bus.inbox(BotId(1))[0].content, "hey");
+    }
+
+    #[test]
+    fn messages_only_reach_teammates_in_range() {
+        let rules = MessageRules {
+            range: Some(2),
+            ..Default::default()
+        };
+        let mut bus = MessageBus::default();
+        bus.post(message("hi"), &rules);
+        bus.deliver(1, &bots(), &rules, manhattan);
+
+        assert_eq!(bus.inbox(BotId(0)).len(), 0);
+        assert_eq!(bus.inbox(BotId(1)).len(), 1);
+        assert_eq!(bus.inbox(BotId(2)).len(), 0);
+        assert_eq!(bus.inbox(BotId(3)).len(), 0);
+    }
+
+    #[test]
+    fn delayed_messages_are_received_later_and_only_once() {
+        let rules = MessageRules {
+            delay: 2,
+            ..Default::default()
+        };
+        let mut bus = MessageBus::default();
+        bus.post(message("hi"), &rules);
+
+        let received: Vec<usize> = (1..6)
+            .map(|tick| {
+                bus.deliver(tick, &bots(), &rules, manhattan);
+                bus.inbox(BotId(1)).len()
+            })
+            .collect();
+        assert_eq!(received, vec![0, 0, 1, 0, 0]);
+    }
+}
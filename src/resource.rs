@@ -1,4 +1,26 @@
+use ruscii::terminal::Color;
+
+use super::bot::Bot;
+
+#[derive(Clone, Debug, Copy, PartialEq, Eq)]
+pub enum ResourceKind {
+    Energy,
+    ShieldRepair,
+    // Sets the tiredness of the bot back to 0
+    TirednessReset,
+}
+
 #[derive(Clone, Debug, Copy)]
 pub struct Resource {
-    pub energy_gain: usize,
+    pub kind: ResourceKind,
+    pub amount: usize,
+    pub expires_at: Option<usize>,
+    // Only bots of this color can collect the resource, anyone can if None
+    pub claimed_by: Option<Color>,
+}
+
+impl Resource {
+    pub fn can_be_collected_by(&self, bot: &Bot) -> bool {
+        self.claimed_by.is_none_or(|color| color == bot.color)
+    }
 }
@@ -0,0 +1,313 @@
+use ruscii::terminal::Color;
+
+use super::{
+    bot::Bot,
+    constants::*,
+    resource::{Resource, ResourceKind},
+    state::{MapEdges, Position},
+    topology::Topology,
+};
+
+/// The configurable rules of a battle
+#[derive(Clone, Debug, Default)]
+pub struct Rules {
+    pub messages: MessageRules,
+    pub friendly_fire: FriendlyFire,
+    pub shield: ShieldRules,
+    pub energy: EnergyRules,
+    pub resources: ResourceRules,
+    // None disables the replicate actuator
+    pub replication: Option<ReplicationRules>,
+    // None makes destroyed bots leave nothing behind
+    pub salvage: Option<SalvageRules>,
+    pub edges: MapEdges,
+    pub topology: Topology,
+    // None lets battles go on forever
+    pub sudden_death: Option<SuddenDeathRules>,
+    pub initiative: Initiative,
+    pub decisions: Decisions,
+}
+
+/// When the strategies decide the actuators of their bots
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Decisions {
+    // Each bot decides on its turn, seeing what the bots before it did in the same tick
+    #[default]
+    OnTurn,
+    // Every bot decides at the start of the tick, seeing the same state
+    Snapshot,
+}
+
+/// The order in which bots take their turns in each tick
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Initiative {
+    // By column from left to right, and by row from bottom to top within each column
+    #[default]
+    ScanOrder,
+    // Shuffled every tick with the battle's random number generator
+    Random,
+    // Teams take turns one bot at a time, and a different team goes first every tick
+    RoundRobin,
+    // Bots with more energy go first, in scan order when they have the same energy
+    ByEnergy,
+}
+
+/// Whether bots can hurt bots of their own color
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum FriendlyFire {
+    #[default]
+    On,
+    Off,
+    // Attacks against allies deal this damage instead of the usual one
+    Reduced(usize),
+}
+
+/// Rules for the team message bus
+#[derive(Clone, Debug)]
+pub struct MessageRules {
+    // Messages longer than this amount of bytes are dropped
+    pub max_size: usize,
+    // Teammates further away than this distance from the sender don't receive the message
+    pub range: Option<usize>,
+    // Extra ticks before a message is delivered, with 0 it's received on the next tick
+    pub delay: usize,
+}
+
+impl Default for MessageRules {
+    fn default() -> Self {
+        MessageRules {
+            max_size: MAX_MESSAGE_SIZE,
+            range: None,
+            delay: 0,
+        }
+    }
+}
+
+/// Rules for recovering shield resistance
+#[derive(Clone, Debug)]
+pub struct ShieldRules {
+    // Ticks without taking damage before the shield starts regenerating, None disables regeneration
+    pub regeneration_delay: Option<usize>,
+    // Shield resistance regenerated each tick once regeneration has started
+    pub regeneration_amount: usize,
+    pub repair_cost: RepairCost,
+    // Shield resistance recovered with the repair actuator
+    pub repair_amount: usize,
+}
+
+impl Default for ShieldRules {
+    fn default() -> Self {
+        ShieldRules {
+            regeneration_delay: None,
+            regeneration_amount: SHIELD_REGENERATION_AMOUNT,
+            repair_cost: RepairCost::Energy(SHIELD_REPAIR_ENERGY_COST),
+            repair_amount: SHIELD_REPAIR_AMOUNT,
+        }
+    }
+}
+
+/// What the repair actuator costs to the bot using it
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RepairCost {
+    // The bot needs more energy than this amount to repair its shield, a full shield costs nothing
+    Energy(usize),
+    // The bot does nothing else this turn
+    Turn,
+}
+
+/// Energy costs of the actions of the bots
+#[derive(Clone, Debug)]
+pub struct EnergyRules {
+    // Per rotation of the shield or the chainsaw
+    pub rotation_cost: usize,
+    // When the chainsaw hits a bot
+    pub attack_cost: usize,
+    // Per successful move, on top of the tiredness
+    pub move_cost: usize,
+    // When the bot doesn't use any actuator in its turn
+    pub idle_cost: usize,
+    // Drained from every bot at the end of each tick
+    pub drain_per_tick: usize,
+    // Bots can't gain energy over this amount
+    pub max_energy: usize,
+}
+
+impl Default for EnergyRules {
+    fn default() -> Self {
+        EnergyRules {
+            rotation_cost: 0,
+            attack_cost: 0,
+            move_cost: 0,
+            idle_cost: 0,
+            drain_per_tick: 0,
+            max_energy: BOTS_STARTING_ENERGY,
+        }
+    }
+}
+
+/// Rules for bots splitting into two
+#[derive(Clone, Debug)]
+pub struct ReplicationRules {
+    // Bots need more energy than this to replicate
+    pub energy_threshold: usize,
+}
+
+impl Default for ReplicationRules {
+    fn default() -> Self {
+        ReplicationRules {
+            energy_threshold: REPLICATION_ENERGY_THRESHOLD,
+        }
+    }
+}
+
+/// Rules for the generation of resources
+#[derive(Clone, Debug)]
+pub struct ResourceRules {
+    pub spawn_policy: SpawnPolicy,
+    // No resources are generated while there are this many on the map
+    pub max_resources: usize,
+    // Relative weights of each kind of resource when generating one
+    pub energy_weight: u32,
+    pub shield_repair_weight: u32,
+    pub tiredness_reset_weight: u32,
+    // Ticks the resources can be collected for after they spawn, with None they stay forever
+    pub expiry: Option<usize>,
+}
+
+impl Default for ResourceRules {
+    fn default() -> Self {
+        ResourceRules {
+            spawn_policy: SpawnPolicy::Uniform {
+                average_per_tick: AVERAGE_RESOURCE_GENERATION_PER_TICK,
+            },
+            max_resources: MAX_RESOURCES,
+            energy_weight: 1,
+            shield_repair_weight: 0,
+            tiredness_reset_weight: 0,
+            expiry: None,
+        }
+    }
+}
+
+/// Where and when resources are generated
+#[derive(Clone, Debug)]
+pub enum SpawnPolicy {
+    // A random amount of resources each tick anywhere in the map
+    Uniform {
+        average_per_tick: usize,
+    },
+    // A random amount of resources each tick close to the resources already in the map
+    Clustered {
+        average_per_tick: usize,
+        radius: usize,
+    },
+    // Every given amount of ticks, a resource grows back in each empty point
+    RegrowthPoints {
+        points: Vec<Position>,
+        every: usize,
+    },
+    // Resources are generated in pairs, symmetric around the center of the map
+    Mirrored {
+        average_per_tick: usize,
+    },
+    // Every given amount of ticks, a wave of resources is generated anywhere in the map
+    Waves {
+        every: usize,
+        amount: usize,
+    },
+}
+
+/// Rules for shrinking the playable area to force battles to end
+///
+/// Cells are grouped in rings going inwards from the edges of the map, the rings that are closed
+/// become hazard cells, and at least the innermost ring always stays open
+#[derive(Clone, Debug)]
+pub struct SuddenDeathRules {
+    // Tick in which the outermost ring closes
+    pub start_tick: usize,
+    // Ticks between the closing of a ring and the next one
+    pub ticks_per_ring: usize,
+    pub penalty: HazardPenalty,
+}
+
+impl SuddenDeathRules {
+    // Number of rings that are closed in the given tick
+    pub fn closed_rings(&self, tick: usize, map_width: usize, map_height: usize) -> usize {
+        if tick < self.start_tick {
+            return 0;
+        }
+        let max_closed_rings = (map_width.min(map_height).saturating_sub(1)) / 2;
+
+        (1 + (tick - self.start_tick) / self.ticks_per_ring.max(1)).min(max_closed_rings)
+    }
+}
+
+impl Default for SuddenDeathRules {
+    fn default() -> Self {
+        SuddenDeathRules {
+            start_tick: SUDDEN_DEATH_START_TICK,
+            ticks_per_ring: SUDDEN_DEATH_TICKS_PER_RING,
+            penalty: HazardPenalty::Damage(HAZARD_DAMAGE),
+        }
+    }
+}
+
+/// What happens to bots that end a tick in a hazard cell
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HazardPenalty {
+    // The bot loses this amount of energy
+    Damage(usize),
+    // The bot is destroyed
+    Death,
+}
+
+/// Rules for the energy resource that destroyed bots leave behind
+///
+/// Salvage is always dropped, even when the map already has the maximum amount of resources,
+/// but it counts towards that maximum for the generation of new resources
+#[derive(Clone, Debug)]
+pub struct SalvageRules {
+    pub value: SalvageValue,
+    // Whether only the team of the killer can collect the salvage
+    pub killer_claims: bool,
+}
+
+impl SalvageRules {
+    pub(crate) fn salvage_of(&self, bot: &Bot, killer: Option<&Bot>) -> Option<Resource> {
+        let amount = match self.value {
+            SalvageValue::Tiredness => bot.tiredness,
+            SalvageValue::Bounty(bounty) => bounty,
+        };
+        if amount == 0 {
+            return None;
+        }
+        let claimed_by: Option<Color> = match killer {
+            Some(killer) if self.killer_claims => Some(killer.color),
+            _ => None,
+        };
+
+        Some(Resource {
+            kind: ResourceKind::Energy,
+            amount,
+            expires_at: None,
+            claimed_by,
+        })
+    }
+}
+
+impl Default for SalvageRules {
+    fn default() -> Self {
+        SalvageRules {
+            value: SalvageValue::Bounty(SALVAGE_BOUNTY),
+            killer_claims: false,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SalvageValue {
+    // The tiredness the bot had accumulated
+    Tiredness,
+    // A fixed amount of energy
+    Bounty(usize),
+}
@@ -0,0 +1,258 @@
+use std::{cmp::Reverse, collections::HashMap};
+
+use rand::{seq::SliceRandom, Rng};
+
+use super::{
+    actuators::{alive_or_destroyed, Actuators},
+    bot::{Bot, BotId},
+    events::{EnergyCost, GameEvent},
+    message::Message,
+    rules::{Decisions, HazardPenalty, Initiative, Rules},
+    spawn::{remove_expired_resources, spawn_resources},
+    state::{GameState, Position},
+};
+
+/// Plays a tick on a copy of the given state with the given actuators, without side effects
+///
+/// This is the same tick logic that battles use, so with the same random number generator state
+/// it gives exactly the same result. Bots without actuators do nothing in their turn.
+pub fn simulate(
+    game_state: &GameState,
+    actuators: &HashMap<BotId, Actuators>,
+    rules: &Rules,
+    rng: &mut impl Rng,
+) -> (GameState, Vec<GameEvent>) {
+    let mut next_state = game_state.clone();
+    let events = play_tick(&mut next_state, rules, rng, |_, bot, _| {
+        actuators.get(&bot.id).cloned()
+    });
+
+    (next_state, events)
+}
+
+// Plays the tick of the given state, asking for the actuators of each bot when it's its turn
+pub(crate) fn play_tick(
+    state: &mut GameState,
+    rules: &Rules,
+    rng: &mut impl Rng,
+    mut decide: impl FnMut(&GameState, &Bot, &Position) -> Option<Actuators>,
+) -> Vec<GameEvent> {
+    let mut events = vec![];
+
+    if let Some(sudden_death) = &rules.sudden_death {
+        state.closed_rings =
+            sudden_death.closed_rings(state.tick, state.map_width(), state.map_height());
+    }
+
+    let (edges, topology) = (state.edges, state.topology);
+    let (map_width, map_height) = (state.map_width(), state.map_height());
+    let bots: Vec<(Position, Bot)> = state
+        .bots()
+        .map(|(position, bot)| (position, *bot))
+        .collect();
+    events.extend(
+        state
+            .messages
+            .deliver(state.tick, &bots, &rules.messages, |from, to| {
+                topology.distance(from, to, edges, map_width, map_height)
+            }),
+    );
+
+    // The turn order is decided at the start of the tick, a bot destroyed before its turn doesn't get to act
+    let turn_order = turn_order(state, rules, rng);
+    events.push(GameEvent::TurnOrder {
+        bots: turn_order.clone(),
+    });
+
+    // Decisions made on the snapshot can't see the hits taken during the tick,
+    // so the bots keep them in their feedback until their next decision
+    let seen_damage: HashMap<BotId, usize> = state
+        .feedback
+        .iter()
+        .map(|(bot_id, feedback)| (*bot_id, feedback.damage_taken.len()))
+        .collect();
+
+    for bot_id in turn_order {
+        let Some((position, bot)) = state.find_bot(bot_id) else {
+            continue;
+        };
+        if bot.is_destroyed() {
+            continue;
+        }
+
+        if let Some(actuators) = decide(state, &bot, &position) {
+            if let Some(content) = actuators.send_message.clone() {
+                let message = Message {
+                    from: bot.id,
+                    color: bot.color,
+                    position: position.clone(),
+                    sent_at: state.tick,
+                    content,
+                };
+                events.push(state.messages.post(message, &rules.messages));
+            }
+
+            let unseen_damage = match (rules.decisions, state.feedback.get(&bot.id)) {
+                (Decisions::Snapshot, Some(feedback)) => {
+                    let seen = seen_damage.get(&bot.id).copied().unwrap_or(0);
+                    feedback.damage_taken[seen..].to_vec()
+                }
+                _ => vec![],
+            };
+
+            events.extend(actuators.execute(position.x, position.y, state, rules));
+            if let Some(feedback) = state.feedback.get_mut(&bot.id) {
+                feedback.damage_taken.splice(0..0, unseen_damage);
+            }
+        }
+    }
+
+    let positions: Vec<Position> = state.bots().map(|(position, _)| position).collect();
+    for position in positions.iter() {
+        let is_hazard = state.is_hazard(position);
+        let Some(bot) = state.bot_at_mut(position) else {
+            continue;
+        };
+        bot.regenerate_shield(&rules.shield);
+
+        if is_hazard {
+            let damage = match rules.sudden_death.as_ref().map(|rules| rules.penalty) {
+                Some(HazardPenalty::Damage(damage)) => damage,
+                Some(HazardPenalty::Death) => bot.energy,
+                None => 0,
+            };
+            events.push(GameEvent::EnergySpent {
+                bot: bot.id,
+                amount: bot.spend_energy(damage),
+                reason: EnergyCost::Hazard,
+            });
+        }
+
+        if rules.energy.drain_per_tick > 0 {
+            events.push(GameEvent::EnergySpent {
+                bot: bot.id,
+                amount: bot.spend_energy(rules.energy.drain_per_tick),
+                reason: EnergyCost::Starvation,
+            });
+        }
+    }
+
+    for position in positions.iter() {
+        if let Some(bot) = state.bot_at(position).copied() {
+            if bot.is_destroyed() {
+                let cell = alive_or_destroyed(bot, None, rules, &mut events);
+                state.set_cell(position, cell);
+            }
+        }
+    }
+    // Resources that expire in 0 ticks are removed as soon as they spawn
+    let tick = state.tick;
+    spawn_resources(state, &rules.resources, tick, rng);
+    remove_expired_resources(state, tick);
+
+    let alive_bots: Vec<BotId> = state.bots().map(|(_, bot)| bot.id).collect();
+    state
+        .feedback
+        .retain(|bot_id, _| alive_bots.contains(bot_id));
+
+    state.tick += 1;
+
+    events
+}
+
+pub(crate) fn turn_order(state: &GameState, rules: &Rules, rng: &mut impl Rng) -> Vec<BotId> {
+    // The bots in the state are always in scan order
+    let mut bots: Vec<Bot> = state.bots().map(|(_, bot)| *bot).collect();
+
+    match rules.initiative {
+        Initiative::ScanOrder => {}
+        Initiative::Random => bots.shuffle(rng),
+        Initiative::ByEnergy => bots.sort_by_key(|bot| Reverse(bot.energy)),
+        Initiative::RoundRobin => {
+            let mut teams: Vec<Vec<Bot>> = state
+                .teams
+                .iter()
+                .map(|color| {
+                    bots.iter()
+                        .filter(|bot| bot.color == *color)
+                        .cloned()
+                        .collect()
+                })
+                .collect();
+            if !teams.is_empty() {
+                let first_team = state.tick % teams.len();
+                teams.rotate_left(first_team);
+            }
+
+            let turns = teams.iter().map(|team| team.len()).max().unwrap_or(0);
+            bots = (0..turns)
+                .flat_map(|turn| teams.iter().filter_map(move |team| team.get(turn)))
+                .cloned()
+                .collect();
+        }
+    }
+
+    bots.into_iter().map(|bot| bot.id).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        battle::Battle,
+        bot::{ColorConfig, Strategy},
+        rules::Decisions,
+        test_helpers::wander,
+    };
+    use ruscii::terminal::Color;
+
+    #[test]
+    fn simulation_matches_the_battle() {
+        let colors = [Color::Blue, Color::Red]
+            .into_iter()
+            .map(|color| ColorConfig {
+                color,
+                number_of_bots: 6,
+                strategy: Strategy::PerBot(wander),
+            })
+            .collect();
+        let rules = Rules {
+            decisions: Decisions::Snapshot,
+            ..Default::default()
+        };
+        let mut battle = Battle::with_seed(colors, rules, 3);
+        let mut rng = battle.rng.clone();
+        let mut state = battle.state.clone();
+
+        for _ in 0..30 {
+            let actuators = state
+                .bots()
+                .map(|(position, bot)| (bot.id, wander(&state, position)))
+                .collect();
+            let (next_state, events) = simulate(&state, &actuators, &battle.rules, &mut rng);
+            state = next_state;
+
+            let events_before = battle.events.len();
+            battle.update();
+
+            assert_eq!(
+                format!("{:?}", events),
+                format!(
+                    "{:?}",
+                    &battle.events[events_before..]
+                        .iter()
+                        .map(|(_, event)| event)
+                        .collect::<Vec<_>>()
+                )
+            );
+            assert_eq!(
+                format!("{:?}", state.bots().collect::<Vec<_>>()),
+                format!("{:?}", battle.state.bots().collect::<Vec<_>>())
+            );
+            assert_eq!(
+                format!("{:?}", state.resources().collect::<Vec<_>>()),
+                format!("{:?}", battle.state.resources().collect::<Vec<_>>())
+            );
+        }
+    }
+}
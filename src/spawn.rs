@@ -0,0 +1,348 @@
+use rand::{distributions::WeightedIndex, prelude::Distribution, Rng};
+
+use super::{
+    constants::*,
+    resource::{Resource, ResourceKind},
+    rules::{ResourceRules, SpawnPolicy},
+    state::{GameCell, GameState, Position},
+};
+
+// Generates the resources for the given tick following the spawn policy of the rules,
+// no resources are generated in the rings closed by sudden death
+pub(crate) fn spawn_resources(
+    state: &mut GameState,
+    rules: &ResourceRules,
+    tick: usize,
+    rng: &mut impl Rng,
+) {
+    let mut remaining = rules
+        .max_resources
+        .saturating_sub(state.resources().count());
+
+    match &rules.spawn_policy {
+        SpawnPolicy::Uniform { average_per_tick } => {
+            for _ in 0..random_amount(*average_per_tick, rng).min(remaining) {
+                if let Some(position) = find_empty_position(state, rng) {
+                    let resource = new_resource(rules, tick, rng);
+                    state.set_cell(&position, GameCell::Resource(resource));
+                }
+            }
+        }
+        SpawnPolicy::Clustered {
+            average_per_tick,
+            radius,
+        } => {
+            for _ in 0..random_amount(*average_per_tick, rng).min(remaining) {
+                let position = find_position_near_resource(state, *radius, rng)
+                    .or_else(|| find_empty_position(state, rng));
+
+                if let Some(position) = position {
+                    let resource = new_resource(rules, tick, rng);
+                    state.set_cell(&position, GameCell::Resource(resource));
+                }
+            }
+        }
+        SpawnPolicy::RegrowthPoints { points, every } => {
+            if *every > 0 && tick.is_multiple_of(*every) {
+                for point in points {
+                    if remaining == 0 {
+                        break;
+                    }
+                    if state.is_hazard(point) {
+                        continue;
+                    }
+                    if let Some(GameCell::Empty) = state.cell_at(point) {
+                        let resource = new_resource(rules, tick, rng);
+                        state.set_cell(point, GameCell::Resource(resource));
+                        remaining -= 1;
+                    }
+                }
+            }
+        }
+        SpawnPolicy::Mirrored { average_per_tick } => {
+            for _ in 0..random_amount(*average_per_tick, rng) {
+                if remaining < 2 {
+                    break;
+                }
+                let Some(position) = find_empty_position(state, rng) else {
+                    break;
+                };
+                let mirrored = Position {
+                    x: state.map_width() - 1 - position.x,
+                    y: state.map_height() - 1 - position.y,
+                };
+
+                if mirrored == position {
+                    continue;
+                }
+                if let Some(GameCell::Empty) = state.cell_at(&mirrored) {
+                    let resource = new_resource(rules, tick, rng);
+                    state.set_cell(&position, GameCell::Resource(resource));
+                    state.set_cell(&mirrored, GameCell::Resource(resource));
+                    remaining -= 2;
+                }
+            }
+        }
+        SpawnPolicy::Waves { every, amount } => {
+            if *every > 0 && tick.is_multiple_of(*every) {
+                for _ in 0..(*amount).min(remaining) {
+                    if let Some(position) = find_empty_position(state, rng) {
+                        let resource = new_resource(rules, tick, rng);
+                        state.set_cell(&position, GameCell::Resource(resource));
+                    }
+                }
+            }
+        }
+    }
+}
+
+// Removes the resources that expire at or before the given tick
+pub(crate) fn remove_expired_resources(state: &mut GameState, tick: usize) {
+    let expired: Vec<Position> = state
+        .resources()
+        .filter(|(_, resource)| {
+            resource
+                .expires_at
+                .is_some_and(|expires_at| expires_at <= tick)
+        })
+        .map(|(position, _)| position)
+        .collect();
+
+    for position in expired {
+        state.set_cell(&position, GameCell::Empty);
+    }
+}
+
+// Empty position outside of the rings closed by sudden death
+pub(crate) fn find_empty_position(state: &GameState, rng: &mut impl Rng) -> Option<Position> {
+    let empty_positions: Vec<Position> = empty_positions(state).collect();
+
+    random_element(empty_positions, rng)
+}
+
+fn find_position_near_resource(
+    state: &GameState,
+    radius: usize,
+    rng: &mut impl Rng,
+) -> Option<Position> {
+    let resources = state.resources().map(|(position, _)| position).collect();
+    let center = random_element(resources, rng)?;
+
+    let near_positions = empty_positions(state)
+        .filter(|position| state.distance(position, &center) <= radius)
+        .collect();
+
+    random_element(near_positions, rng)
+}
+
+fn empty_positions(state: &GameState) -> impl Iterator<Item = Position> + '_ {
+    state.positions().filter(|position| {
+        matches!(state.cell_at(position), Some(GameCell::Empty)) && !state.is_hazard(position)
+    })
+}
+
+fn random_element(mut positions: Vec<Position>, rng: &mut impl Rng) -> Option<Position> {
+    if positions.is_empty() {
+        return None;
+    }
+    let index = rng.gen_range(0..positions.len());
+
+    Some(positions.swap_remove(index))
+}
+
+// Random amount of resources between 0 and twice the average, excluded
+fn random_amount(average_per_tick: usize, rng: &mut impl Rng) -> usize {
+    if average_per_tick == 0 {
+        return 0;
+    }
+
+    rng.gen_range(0..(average_per_tick * 2))
+}
+
+fn new_resource(rules: &ResourceRules, tick: usize, rng: &mut impl Rng) -> Resource {
+    let weights = [
+        rules.energy_weight,
+        rules.shield_repair_weight,
+        rules.tiredness_reset_weight,
+    ];
+    let kind = match WeightedIndex::new(weights).map(|index| index.sample(rng)) {
+        Ok(1) => ResourceKind::ShieldRepair,
+        Ok(2) => ResourceKind::TirednessReset,
+        _ => ResourceKind::Energy,
+    };
+
+    let amount = match kind {
+        ResourceKind::Energy => rng.gen_range(RESOURCE_MIN_ENERGY_GAIN..RESOURCE_MAX_ENERGY_GAIN),
+        ResourceKind::ShieldRepair => {
+            rng.gen_range(RESOURCE_MIN_SHIELD_REPAIR..RESOURCE_MAX_SHIELD_REPAIR)
+        }
+        ResourceKind::TirednessReset => 0,
+    };
+
+    Resource {
+        kind,
+        amount,
+        expires_at: rules.expiry.map(|expiry| tick + expiry),
+        claimed_by: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, SeedableRng};
+    use ruscii::terminal::Color;
+
+    use super::*;
+    use crate::{
+        bot::{Bot, BotId},
+        rules::Rules,
+        simulation::simulate,
+        test_helpers::state_with_bots,
+    };
+
+    fn rules(spawn_policy: SpawnPolicy) -> ResourceRules {
+        ResourceRules {
+            spawn_policy,
+            ..Default::default()
+        }
+    }
+
+    fn resource_positions(state: &GameState) -> Vec<Position> {
+        state.resources().map(|(position, _)| position).collect()
+    }
+
+    #[test]
+    fn uniform_spawning_stops_at_the_maximum_resources() {
+        let mut state = state_with_bots(vec![]);
+        let rules = ResourceRules {
+            max_resources: 5,
+            ..rules(SpawnPolicy::Uniform {
+                average_per_tick: 3,
+            })
+        };
+        let mut rng = StdRng::seed_from_u64(0);
+
+        for tick in 0..20 {
+            spawn_resources(&mut state, &rules, tick, &mut rng);
+            assert!(state.resources().count() <= 5);
+        }
+        assert_eq!(state.resources().count(), 5);
+    }
+
+    #[test]
+    fn clustered_resources_spawn_close_to_each_other() {
+        let mut state = state_with_bots(vec![]);
+        let rules = rules(SpawnPolicy::Clustered {
+            average_per_tick: 3,
+            radius: 2,
+        });
+        let mut rng = StdRng::seed_from_u64(0);
+
+        for tick in 0..5 {
+            spawn_resources(&mut state, &rules, tick, &mut rng);
+        }
+        let positions = resource_positions(&state);
+        assert!(positions.len() > 1);
+        for position in positions.iter() {
+            assert!(positions
+                .iter()
+                .any(|other| other != position && state.distance(position, other) <= 2));
+        }
+    }
+
+    #[test]
+    fn regrowth_points_regrow_every_few_ticks_where_they_are_empty() {
+        let points = vec![
+            Position { x: 1, y: 1 },
+            Position { x: 2, y: 2 },
+            Position { x: 3, y: 3 },
+        ];
+        let mut state = state_with_bots(vec![(
+            Position { x: 3, y: 3 },
+            Bot::new(BotId(0), Color::Blue),
+        )]);
+        let rules = rules(SpawnPolicy::RegrowthPoints {
+            points: points.clone(),
+            every: 3,
+        });
+        let mut rng = StdRng::seed_from_u64(0);
+
+        spawn_resources(&mut state, &rules, 1, &mut rng);
+        assert!(resource_positions(&state).is_empty());
+
+        spawn_resources(&mut state, &rules, 3, &mut rng);
+        assert_eq!(resource_positions(&state), points[..2]);
+    }
+
+    #[test]
+    fn mirrored_resources_spawn_in_symmetric_pairs() {
+        let mut state = state_with_bots(vec![]);
+        let rules = rules(SpawnPolicy::Mirrored {
+            average_per_tick: 3,
+        });
+        let mut rng = StdRng::seed_from_u64(0);
+
+        for tick in 0..5 {
+            spawn_resources(&mut state, &rules, tick, &mut rng);
+        }
+        let positions = resource_positions(&state);
+        assert!(!positions.is_empty());
+        for position in positions.iter() {
+            assert!(positions.contains(&Position {
+                x: MAP_WIDTH - 1 - position.x,
+                y: MAP_HEIGHT - 1 - position.y,
+            }));
+        }
+    }
+
+    #[test]
+    fn waves_spawn_the_whole_amount_every_few_ticks() {
+        let mut state = state_with_bots(vec![]);
+        let rules = rules(SpawnPolicy::Waves {
+            every: 4,
+            amount: 6,
+        });
+        let mut rng = StdRng::seed_from_u64(0);
+
+        spawn_resources(&mut state, &rules, 2, &mut rng);
+        assert_eq!(state.resources().count(), 0);
+
+        spawn_resources(&mut state, &rules, 4, &mut rng);
+        assert_eq!(state.resources().count(), 6);
+    }
+
+    // Plays ticks without bots, with a wave of resources on the first tick and none after it
+    fn resources_after_ticks(expiry: Option<usize>, ticks: usize) -> usize {
+        let rules = Rules {
+            resources: ResourceRules {
+                expiry,
+                ..rules(SpawnPolicy::Waves {
+                    every: 100,
+                    amount: 3,
+                })
+            },
+            ..Default::default()
+        };
+        let mut state = state_with_bots(vec![]);
+        let mut rng = StdRng::seed_from_u64(0);
+
+        for _ in 0..ticks {
+            state = simulate(&state, &Default::default(), &rules, &mut rng).0;
+        }
+
+        state.resources().count()
+    }
+
+    #[test]
+    fn resources_can_be_collected_for_the_expiry_ticks() {
+        assert_eq!(resources_after_ticks(Some(0), 1), 0);
+
+        assert_eq!(resources_after_ticks(Some(1), 1), 3);
+        assert_eq!(resources_after_ticks(Some(1), 2), 0);
+
+        assert_eq!(resources_after_ticks(Some(2), 2), 3);
+        assert_eq!(resources_after_ticks(Some(2), 3), 0);
+
+        assert_eq!(resources_after_ticks(None, 10), 3);
+    }
+}
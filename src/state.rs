@@ -1,56 +1,417 @@
-use super::{bot::Bot, constants::*, resource::Resource};
+use std::collections::{BTreeSet, HashMap};
 
-#[derive(Clone, Copy)]
+use ruscii::terminal::Color;
+
+use super::{
+    bot::{Bot, BotId},
+    constants::*,
+    direction::Direction,
+    feedback::ActionFeedback,
+    message::{Message, MessageBus},
+    resource::Resource,
+    topology::Topology,
+};
+
+#[derive(Clone, Copy, Debug)]
 pub enum GameCell {
     Empty,
     Bot(Bot),
     Resource(Resource),
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Position {
     pub x: usize,
     pub y: usize,
 }
 
+impl Position {
+    // How many rings of cells there are between this position and the edges of the map
+    pub fn ring(&self, map_width: usize, map_height: usize) -> usize {
+        self.x
+            .min(self.y)
+            .min(map_width.saturating_sub(self.x + 1))
+            .min(map_height.saturating_sub(self.y + 1))
+    }
+}
+
+/// What happens at the edges of the map
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum MapEdges {
+    // Bots can't go past the edges
+    #[default]
+    Bounded,
+    // Going past an edge leads to the opposite edge
+    Toroidal,
+}
+
+impl MapEdges {
+    // Brings the given coordinates back inside the map, returns None if they fall outside of a bounded map
+    pub fn wrap(
+        &self,
+        x: isize,
+        y: isize,
+        map_width: usize,
+        map_height: usize,
+    ) -> Option<Position> {
+        match self {
+            MapEdges::Bounded => {
+                if x < 0 || y < 0 || x >= map_width as isize || y >= map_height as isize {
+                    None
+                } else {
+                    Some(Position {
+                        x: x as usize,
+                        y: y as usize,
+                    })
+                }
+            }
+            MapEdges::Toroidal => Some(Position {
+                x: x.rem_euclid(map_width as isize) as usize,
+                y: y.rem_euclid(map_height as isize) as usize,
+            }),
+        }
+    }
+
+    // Shortest distance between the two positions along each axis
+    pub fn axis_distances(
+        &self,
+        from: &Position,
+        to: &Position,
+        map_width: usize,
+        map_height: usize,
+    ) -> (usize, usize) {
+        let x_distance = from.x.abs_diff(to.x);
+        let y_distance = from.y.abs_diff(to.y);
+
+        match self {
+            MapEdges::Bounded => (x_distance, y_distance),
+            MapEdges::Toroidal => (
+                x_distance.min(map_width - x_distance),
+                y_distance.min(map_height - y_distance),
+            ),
+        }
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct GameState {
-    pub map_width: usize,
-    pub map_height: usize,
-    pub bots: Vec<(Position, Bot)>,
-    pub resources: Vec<(Position, Resource)>,
+    map_width: usize,
+    map_height: usize,
+    pub edges: MapEdges,
+    pub topology: Topology,
+    // Indexed by column and then by row, see cell_index
+    cells: Vec<GameCell>,
+    bot_positions: HashMap<BotId, Position>,
+    // Indexes of the cells with a bot or a resource, sorted in scan order like the cells
+    occupied_cells: BTreeSet<usize>,
+    pub(crate) messages: MessageBus,
+    pub(crate) feedback: HashMap<BotId, ActionFeedback>,
+    pub(crate) next_bot_id: usize,
+    // Rings of cells closed by sudden death, counting from the edges of the map
+    pub(crate) closed_rings: usize,
+    // Tick that is played next, and the teams in the order of the battle's color configs
+    pub(crate) tick: usize,
+    pub(crate) teams: Vec<Color>,
 }
 
-pub(crate) fn state_to_matrix(state: GameState) -> [[GameCell; MAP_HEIGHT]; MAP_WIDTH] {
-    let mut map = [[GameCell::Empty; MAP_HEIGHT]; MAP_WIDTH];
+impl GameState {
+    pub fn map_width(&self) -> usize {
+        self.map_width
+    }
+
+    pub fn map_height(&self) -> usize {
+        self.map_height
+    }
+
+    // Returns None if the position is outside of the map
+    pub fn cell_at(&self, position: &Position) -> Option<&GameCell> {
+        self.cell_index(position).map(|index| &self.cells[index])
+    }
+
+    pub fn bot_at(&self, position: &Position) -> Option<&Bot> {
+        match self.cell_at(position) {
+            Some(GameCell::Bot(bot)) => Some(bot),
+            _ => None,
+        }
+    }
+
+    pub fn resource_at(&self, position: &Position) -> Option<&Resource> {
+        match self.cell_at(position) {
+            Some(GameCell::Resource(resource)) => Some(resource),
+            _ => None,
+        }
+    }
+
+    // Returns None if the bot is not on the map anymore
+    pub fn bot(&self, bot_id: BotId) -> Option<(Position, &Bot)> {
+        let position = self.bot_positions.get(&bot_id)?;
+
+        self.bot_at(position).map(|bot| (position.clone(), bot))
+    }
+
+    // Bots on the map in scan order: by column from left to right, and by row from bottom to top
+    pub fn bots(&self) -> impl Iterator<Item = (Position, &Bot)> + '_ {
+        self.occupied().filter_map(|(position, cell)| match cell {
+            GameCell::Bot(bot) => Some((position, bot)),
+            _ => None,
+        })
+    }
+
+    // Resources on the map in scan order
+    pub fn resources(&self) -> impl Iterator<Item = (Position, &Resource)> + '_ {
+        self.occupied().filter_map(|(position, cell)| match cell {
+            GameCell::Resource(resource) => Some((position, resource)),
+            _ => None,
+        })
+    }
+
+    pub fn bot_count(&self) -> usize {
+        self.bot_positions.len()
+    }
+
+    // Messages from teammates that the given bot received this tick
+    pub fn inbox(&self, bot_id: BotId) -> &[Message] {
+        self.messages.inbox(bot_id)
+    }
+
+    // Outcome of the last actuators of the given bot, and the damage it took since then
+    pub fn feedback(&self, bot_id: BotId) -> Option<&ActionFeedback> {
+        self.feedback.get(&bot_id)
+    }
+
+    // Tick that is played next, starting from 0
+    pub fn tick(&self) -> usize {
+        self.tick
+    }
+
+    // Hazard cells are outside of the arena after it starts shrinking,
+    // bots that end a tick in them suffer the sudden death penalty
+    pub fn is_hazard(&self, position: &Position) -> bool {
+        position.ring(self.map_width, self.map_height) < self.closed_rings
+    }
+
+    // Returns None if the adjacent position falls outside of the map,
+    // or if the direction is not available in the map's topology
+    pub fn adjacent_position(&self, position: &Position, direction: Direction) -> Option<Position> {
+        self.topology.adjacent_position(
+            position,
+            direction,
+            self.edges,
+            self.map_width,
+            self.map_height,
+        )
+    }
+
+    // Positions adjacent to the given one that are inside the map, along with their direction
+    pub fn adjacent_positions(&self, position: &Position) -> Vec<(Direction, Position)> {
+        self.topology
+            .directions()
+            .into_iter()
+            .filter_map(|direction| {
+                self.adjacent_position(position, direction)
+                    .map(|adjacent| (direction, adjacent))
+            })
+            .collect()
+    }
+
+    // Number of steps a bot needs to go from one position to the other
+    pub fn distance(&self, from: &Position, to: &Position) -> usize {
+        self.topology
+            .distance(from, to, self.edges, self.map_width, self.map_height)
+    }
+
+    // Bots of the same color as the given one, not including itself
+    pub fn allies_of(&self, bot: &Bot) -> Vec<(Position, Bot)> {
+        self.bots()
+            .filter(|(_, other)| other.id != bot.id && other.is_ally_of(bot))
+            .map(|(position, other)| (position, *other))
+            .collect()
+    }
+
+    pub fn enemies_of(&self, bot: &Bot) -> Vec<(Position, Bot)> {
+        self.bots()
+            .filter(|(_, other)| !other.is_ally_of(bot))
+            .map(|(position, other)| (position, *other))
+            .collect()
+    }
+
+    pub(crate) fn new_bot_id(&mut self) -> BotId {
+        let bot_id = BotId(self.next_bot_id);
+        self.next_bot_id += 1;
 
-    for (pos, bot) in state.bots {
-        map[pos.x][pos.y] = GameCell::Bot(bot);
+        bot_id
     }
-    for (pos, resource) in state.resources {
-        map[pos.x][pos.y] = GameCell::Resource(resource);
+
+    pub(crate) fn find_bot(&self, bot_id: BotId) -> Option<(Position, Bot)> {
+        self.bot(bot_id).map(|(position, bot)| (position, *bot))
     }
 
-    map
+    pub(crate) fn bot_at_mut(&mut self, position: &Position) -> Option<&mut Bot> {
+        let index = self.cell_index(position)?;
+
+        match &mut self.cells[index] {
+            GameCell::Bot(bot) => Some(bot),
+            _ => None,
+        }
+    }
+
+    // Replaces the contents of the cell, keeping track of where each bot is
+    pub(crate) fn set_cell(&mut self, position: &Position, cell: GameCell) {
+        let Some(index) = self.cell_index(position) else {
+            return;
+        };
+
+        if let GameCell::Bot(bot) = self.cells[index] {
+            self.bot_positions.remove(&bot.id);
+        }
+        if let GameCell::Bot(bot) = cell {
+            self.bot_positions.insert(bot.id, position.clone());
+        }
+        match cell {
+            GameCell::Empty => self.occupied_cells.remove(&index),
+            _ => self.occupied_cells.insert(index),
+        };
+        self.cells[index] = cell;
+    }
+
+    // Cells with a bot or a resource in scan order, without going through the empty ones
+    fn occupied(&self) -> impl Iterator<Item = (Position, &GameCell)> + '_ {
+        self.occupied_cells.iter().map(|index| {
+            let position = Position {
+                x: index / self.map_height,
+                y: index % self.map_height,
+            };
+
+            (position, &self.cells[*index])
+        })
+    }
+
+    fn cell_index(&self, position: &Position) -> Option<usize> {
+        if position.x >= self.map_width || position.y >= self.map_height {
+            return None;
+        }
+
+        Some(position.x * self.map_height + position.y)
+    }
+
+    // Every position of the map in scan order
+    pub(crate) fn positions(&self) -> impl Iterator<Item = Position> {
+        let map_height = self.map_height;
+
+        (0..self.map_width).flat_map(move |x| (0..map_height).map(move |y| Position { x, y }))
+    }
 }
 
 pub(crate) fn from_matrix(matrix: [[GameCell; MAP_HEIGHT]; MAP_WIDTH]) -> GameState {
     let mut state = GameState {
-        bots: vec![],
-        resources: vec![],
+        cells: vec![GameCell::Empty; MAP_WIDTH * MAP_HEIGHT],
+        bot_positions: HashMap::new(),
+        occupied_cells: BTreeSet::new(),
         map_width: MAP_WIDTH,
         map_height: MAP_HEIGHT,
+        edges: MapEdges::default(),
+        topology: Topology::default(),
+        messages: MessageBus::default(),
+        feedback: HashMap::new(),
+        next_bot_id: 0,
+        closed_rings: 0,
+        tick: 0,
+        teams: vec![],
     };
 
-    for x in 0..matrix.len() {
-        for y in 0..matrix[0].len() {
-            match matrix[x][y] {
-                GameCell::Bot(bot) => state.bots.push((Position { x, y }, bot)),
-                GameCell::Resource(resource) => state.resources.push((Position { x, y }, resource)),
-                _ => {}
-            }
+    for (x, column) in matrix.iter().enumerate() {
+        for (y, cell) in column.iter().enumerate() {
+            state.set_cell(&Position { x, y }, *cell);
         }
     }
+    state.next_bot_id = state.bots().map(|(_, bot)| bot.id.0 + 1).max().unwrap_or(0);
 
     state
 }
+
+#[cfg(test)]
+mod tests {
+    use ruscii::terminal::Color;
+
+    use super::*;
+    use crate::resource::ResourceKind;
+
+    #[test]
+    fn lookups_follow_bots_when_cells_change() {
+        let mut state = from_matrix([[GameCell::Empty; MAP_HEIGHT]; MAP_WIDTH]);
+        let bot = Bot::new(BotId(0), Color::Blue);
+        let from = Position { x: 2, y: 3 };
+        let to = Position { x: 2, y: 4 };
+
+        state.set_cell(&from, GameCell::Bot(bot));
+        state.set_cell(&from, GameCell::Empty);
+        state.set_cell(&to, GameCell::Bot(bot));
+
+        assert!(state.bot_at(&from).is_none());
+        assert_eq!(state.bot(BotId(0)).map(|(position, _)| position), Some(to));
+        assert_eq!(state.bots().count(), 1);
+        assert!(state.cell_at(&Position { x: MAP_WIDTH, y: 0 }).is_none());
+    }
+
+    #[test]
+    fn bots_and_resources_are_listed_in_scan_order() {
+        let mut state = from_matrix([[GameCell::Empty; MAP_HEIGHT]; MAP_WIDTH]);
+        let resource = Resource {
+            kind: ResourceKind::Energy,
+            amount: 1,
+            expires_at: None,
+            claimed_by: None,
+        };
+        state.set_cell(
+            &Position { x: 3, y: 0 },
+            GameCell::Bot(Bot::new(BotId(0), Color::Blue)),
+        );
+        state.set_cell(
+            &Position { x: 1, y: 5 },
+            GameCell::Bot(Bot::new(BotId(1), Color::Red)),
+        );
+        state.set_cell(&Position { x: 1, y: 2 }, GameCell::Resource(resource));
+        state.set_cell(
+            &Position { x: 1, y: 4 },
+            GameCell::Bot(Bot::new(BotId(2), Color::Red)),
+        );
+
+        let ids: Vec<BotId> = state.bots().map(|(_, bot)| bot.id).collect();
+        assert_eq!(ids, vec![BotId(2), BotId(1), BotId(0)]);
+        assert_eq!(state.resources().count(), 1);
+
+        state.set_cell(&Position { x: 1, y: 2 }, GameCell::Empty);
+        assert_eq!(state.resources().count(), 0);
+        assert_eq!(state.bots().count(), 3);
+    }
+
+    #[test]
+    fn toroidal_maps_wrap_distances_and_neighbors_around_the_edges() {
+        let mut state = from_matrix([[GameCell::Empty; MAP_HEIGHT]; MAP_WIDTH]);
+        let corner = Position { x: 0, y: 0 };
+        let opposite_corner = Position {
+            x: MAP_WIDTH - 1,
+            y: MAP_HEIGHT - 1,
+        };
+        assert_eq!(state.adjacent_positions(&corner).len(), 2);
+        assert_eq!(
+            state.distance(&corner, &opposite_corner),
+            MAP_WIDTH + MAP_HEIGHT - 2
+        );
+
+        state.edges = MapEdges::Toroidal;
+        let neighbors = state.adjacent_positions(&corner);
+        let left = Position {
+            x: MAP_WIDTH - 1,
+            y: 0,
+        };
+        let below = Position {
+            x: 0,
+            y: MAP_HEIGHT - 1,
+        };
+        assert_eq!(neighbors.len(), 4);
+        assert!(neighbors.contains(&(Direction::Left, left)));
+        assert!(neighbors.contains(&(Direction::Down, below)));
+        assert_eq!(state.distance(&corner, &opposite_corner), 2);
+    }
+}
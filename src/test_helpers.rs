@@ -0,0 +1,35 @@
+use super::{
+    actuators::Actuators,
+    bot::Bot,
+    constants::*,
+    direction::Rotation,
+    state::{from_matrix, GameCell, GameState, Position},
+};
+
+// Empty map with only the given bots on it
+pub(crate) fn state_with_bots(bots: Vec<(Position, Bot)>) -> GameState {
+    let mut map = [[GameCell::Empty; MAP_HEIGHT]; MAP_WIDTH];
+    for (position, bot) in bots {
+        map[position.x][position.y] = GameCell::Bot(bot);
+    }
+
+    from_matrix(map)
+}
+
+pub(crate) fn idle(_game_state: &GameState, _bot_position: Position) -> Actuators {
+    Actuators::default()
+}
+
+// Swings the chainsaw every tick and wanders around, so that every actuator gets exercised
+pub(crate) fn wander(game_state: &GameState, bot_position: Position) -> Actuators {
+    let directions = game_state.topology.directions();
+    let bot = game_state.bot_at(&bot_position);
+    let index = bot_position.x * 7 + bot_position.y * 3 + bot.map_or(0, |bot| bot.energy);
+
+    Actuators {
+        rotate_chainsaw: Some(Rotation::Clockwise),
+        rotate_shield: Some(Rotation::Counterclockwise),
+        move_bot: Some(directions[index % directions.len()]),
+        ..Default::default()
+    }
+}
@@ -0,0 +1,237 @@
+use super::{
+    direction::{Direction, Rotation, CLOCKWISE},
+    state::{MapEdges, Position},
+};
+
+/// The shape of the cells of the map, and how they connect to each other
+///
+/// Hex maps use the same coordinates as square ones, with odd rows shifted half a cell to the right
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Topology {
+    // Square cells, connected up, down, right and left
+    #[default]
+    Square4,
+    // Square cells, also connected diagonally
+    Square8,
+    // Hexagonal cells, connected right, left and diagonally
+    Hex,
+}
+
+impl Topology {
+    // The directions bots can face and move in, in clockwise order
+    pub fn directions(&self) -> Vec<Direction> {
+        CLOCKWISE
+            .into_iter()
+            .filter(|direction| self.contains(*direction))
+            .collect()
+    }
+
+    pub fn contains(&self, direction: Direction) -> bool {
+        match self {
+            Topology::Square4 => !direction.is_diagonal(),
+            Topology::Square8 => true,
+            Topology::Hex => !matches!(direction, Direction::Up | Direction::Down),
+        }
+    }
+
+    // The closest available direction to the given one, going clockwise
+    pub fn closest_direction(&self, direction: Direction) -> Direction {
+        let mut closest = direction;
+        while !self.contains(closest) {
+            closest = closest.rotate_eighth(Rotation::Clockwise);
+        }
+
+        closest
+    }
+
+    // Rotates to the next available direction
+    pub fn rotate(&self, direction: Direction, rotation: Rotation) -> Direction {
+        let directions = self.directions();
+        let index = self.index_of(direction);
+
+        match rotation {
+            Rotation::Clockwise => directions[(index + 1) % directions.len()],
+            Rotation::Counterclockwise => {
+                directions[(index + directions.len() - 1) % directions.len()]
+            }
+        }
+    }
+
+    // Shortest rotation to go from one direction to the other, counterclockwise if both are as short
+    pub fn rotation_towards(&self, from: Direction, to: Direction) -> Option<Rotation> {
+        let steps = self.clockwise_steps(from, to);
+
+        if steps == 0 {
+            None
+        } else if steps * 2 < self.directions().len() {
+            Some(Rotation::Clockwise)
+        } else {
+            Some(Rotation::Counterclockwise)
+        }
+    }
+
+    // Whether a shield facing the given direction blocks an attack coming from the other one,
+    // shields cover every direction at most 60 degrees away from them
+    pub fn shield_covers(&self, shield_direction: Direction, attack_from: Direction) -> bool {
+        let steps = self.clockwise_steps(shield_direction, attack_from);
+        let steps = steps.min(self.directions().len() - steps);
+
+        steps * 360 <= 60 * self.directions().len()
+    }
+
+    // Returns None if the adjacent position falls outside of a bounded map,
+    // or if the direction is not available in this topology
+    pub fn adjacent_position(
+        &self,
+        position: &Position,
+        direction: Direction,
+        edges: MapEdges,
+        map_width: usize,
+        map_height: usize,
+    ) -> Option<Position> {
+        if !self.contains(direction) {
+            return None;
+        }
+
+        let (dx, dy) = match self {
+            Topology::Hex if direction.is_diagonal() => {
+                // Odd rows are shifted to the right, so going diagonally from them goes further right
+                let (dx, dy) = direction.offset();
+                let shift = (position.y % 2) as isize;
+
+                (if dx > 0 { shift } else { shift - 1 }, dy)
+            }
+            _ => direction.offset(),
+        };
+
+        edges.wrap(
+            position.x as isize + dx,
+            position.y as isize + dy,
+            map_width,
+            map_height,
+        )
+    }
+
+    // Number of steps a bot needs to go from one position to the other
+    pub fn distance(
+        &self,
+        from: &Position,
+        to: &Position,
+        edges: MapEdges,
+        map_width: usize,
+        map_height: usize,
+    ) -> usize {
+        match self {
+            Topology::Square4 => {
+                let (x_distance, y_distance) =
+                    edges.axis_distances(from, to, map_width, map_height);
+
+                x_distance + y_distance
+            }
+            Topology::Square8 => {
+                let (x_distance, y_distance) =
+                    edges.axis_distances(from, to, map_width, map_height);
+
+                x_distance.max(y_distance)
+            }
+            Topology::Hex => {
+                let copies: &[isize] = match edges {
+                    MapEdges::Bounded => &[0],
+                    MapEdges::Toroidal => &[-1, 0, 1],
+                };
+                let from = (from.x as isize, from.y as isize);
+
+                // On toroidal maps the closest copy of the target may be across the edges
+                copies
+                    .iter()
+                    .flat_map(|x_copy| copies.iter().map(move |y_copy| (*x_copy, *y_copy)))
+                    .map(|(x_copy, y_copy)| {
+                        let to = (
+                            to.x as isize + x_copy * map_width as isize,
+                            to.y as isize + y_copy * map_height as isize,
+                        );
+
+                        hex_distance(from, to)
+                    })
+                    .min()
+                    .unwrap_or(0)
+            }
+        }
+    }
+
+    fn index_of(&self, direction: Direction) -> usize {
+        let direction = self.closest_direction(direction);
+
+        self.directions()
+            .iter()
+            .position(|available| *available == direction)
+            .unwrap_or(0)
+    }
+
+    fn clockwise_steps(&self, from: Direction, to: Direction) -> usize {
+        let count = self.directions().len();
+
+        (self.index_of(to) + count - self.index_of(from)) % count
+    }
+}
+
+// Converts both positions to axial coordinates to compute the distance between them
+fn hex_distance(from: (isize, isize), to: (isize, isize)) -> usize {
+    let axial = |(x, y): (isize, isize)| (x - (y - y.rem_euclid(2)) / 2, y);
+    let (from_q, from_r) = axial(from);
+    let (to_q, to_r) = axial(to);
+    let (dq, dr) = (to_q - from_q, to_r - from_r);
+
+    ((dq.abs() + dr.abs() + (dq + dr).abs()) / 2) as usize
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::{MAP_HEIGHT, MAP_WIDTH};
+
+    #[test]
+    fn hex_neighbors_are_one_step_away() {
+        for position in [Position { x: 4, y: 4 }, Position { x: 4, y: 5 }] {
+            let neighbors: Vec<Position> = Topology::Hex
+                .directions()
+                .into_iter()
+                .filter_map(|direction| {
+                    Topology::Hex.adjacent_position(
+                        &position,
+                        direction,
+                        MapEdges::Bounded,
+                        MAP_WIDTH,
+                        MAP_HEIGHT,
+                    )
+                })
+                .collect();
+
+            assert_eq!(neighbors.len(), 6);
+            for neighbor in neighbors {
+                let distance = Topology::Hex.distance(
+                    &position,
+                    &neighbor,
+                    MapEdges::Bounded,
+                    MAP_WIDTH,
+                    MAP_HEIGHT,
+                );
+                assert_eq!(distance, 1);
+            }
+        }
+    }
+
+    #[test]
+    fn rotations_go_through_every_direction() {
+        for topology in [Topology::Square4, Topology::Square8, Topology::Hex] {
+            let start = topology.closest_direction(Direction::Up);
+            let mut direction = start;
+
+            for _ in 0..topology.directions().len() {
+                direction = topology.rotate(direction, Rotation::Clockwise);
+                assert!(topology.contains(direction));
+            }
+            assert_eq!(direction, start);
+        }
+    }
+}